@@ -0,0 +1,138 @@
+use serde::{Serialize, Deserialize};
+
+use crate::compiler_message::CompilerMessageTarget;
+
+
+/// The messages Cargo reports about the build itself, rather than the code being compiled.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum CargoEvent {
+  CompilerArtifact(Box<CompilerArtifact>),
+  BuildScriptExecuted(BuildScriptExecuted),
+  BuildFinished(BuildFinished),
+  /// Cargo doesn't report build script failures as JSON, so this is decoded from its text output
+  BuildScriptFailed(BuildScriptFailed),
+  /// Cargo doesn't report crates that failed to compile as JSON, so this is decoded from its text output
+  CouldNotCompile(CouldNotCompile),
+}
+
+
+/// Reported for each target Cargo has built, or found to be up to date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompilerArtifact {
+  /// Example: path+file:///home/user/projects/quiet#0.3.9 or libc 0.2.116 (registry+https://github.com/rust-lang/crates.io-index)
+  pub package_id: String,
+  pub target: CompilerMessageTarget,
+  #[serde(default)]
+  pub features: Vec<String>,
+  #[serde(default)]
+  pub filenames: Vec<String>,
+  pub executable: Option<String>,
+  /// Set when the target was up to date and didn't need to be built
+  pub fresh: bool,
+}
+
+impl CompilerArtifact {
+
+  /// Whether the package is in the workspace or a path dependency, rather than from a registry or git.
+  pub fn is_local(&self) -> bool {
+    self.package_id.starts_with("path+file://") || self.package_id.contains("(path+file://")
+  }
+
+  pub fn package_name(&self) -> String {
+    package_name(&self.package_id)
+  }
+}
+
+
+/// Gets the name of the package from a package id, which comes in two formats:
+///   - libc 0.2.116 (registry+https://github.com/rust-lang/crates.io-index)
+///   - path+file:///home/user/projects/quiet#0.3.9 or registry+https://github.com/rust-lang/crates.io-index#libc@0.2.116
+///
+/// The second format only includes the name when it differs from the last component of the path.
+pub fn package_name(package_id: &str) -> String {
+  match package_id.split_once('#') {
+    Some((source, fragment)) => {
+      match fragment.split_once('@') {
+        Some((name, _)) => name.to_owned(),
+        None => source.trim_end_matches('/').rsplit('/').next().unwrap_or(source).to_owned(),
+      }
+    },
+    None => package_id.split_whitespace().next().unwrap_or(package_id).to_owned(),
+  }
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildScriptExecuted {
+  pub package_id: String,
+  #[serde(default)]
+  pub linked_libs: Vec<String>,
+  #[serde(default)]
+  pub linked_paths: Vec<String>,
+  #[serde(default)]
+  pub cfgs: Vec<String>,
+  #[serde(default)]
+  pub env: Vec<(String, String)>,
+  pub out_dir: Option<String>,
+}
+
+
+/// The last message Cargo reports once it has finished building, before any tests are run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BuildFinished {
+  pub success: bool,
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildScriptFailed {
+  /// Example: build-failure v0.1.0 (/home/user/projects/build-failure)
+  pub package: String,
+}
+
+impl BuildScriptFailed {
+
+  const PREFIX: &'static str = "error: failed to run custom build command for `";
+
+  /// Decodes a line such as: error: failed to run custom build command for `foo v0.1.0 (/path/to/foo)`
+  pub fn from_line(line: &str) -> Option<Self> {
+    line
+      .trim()
+      .strip_prefix(Self::PREFIX)
+      .and_then(|rest| rest.split_once('`'))
+      .map(|(package, _)| Self { package: package.to_owned() })
+  }
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CouldNotCompile {
+  pub package: String,
+  /// The target that failed, when Cargo reports it. Example: bin "quiet"
+  pub target: Option<String>,
+}
+
+impl CouldNotCompile {
+
+  const PREFIX: &'static str = "error: could not compile `";
+
+  /// Decodes a line such as: error: could not compile `foo` (bin "foo") due to 2 previous errors
+  ///
+  /// Older versions of Cargo leave out the target: error: could not compile `foo` due to 2 previous errors
+  pub fn from_line(line: &str) -> Option<Self> {
+    let (package, rest) =
+      line
+        .trim()
+        .strip_prefix(Self::PREFIX)?
+        .split_once('`')?;
+
+    let target =
+      rest
+        .strip_prefix(" (")
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(target, _)| target.to_owned());
+
+    Some(Self { package: package.to_owned(), target })
+  }
+}
@@ -1,4 +1,6 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use crate::file_pattern::{parse_file_pattern, parse_line_range, FilePattern, LineRange};
 
 /// Turn the volume down on Cargo errors and warnings.
 ///
@@ -9,6 +11,9 @@ use clap::Parser;
 /// For example:
 /// cargo test --message-format json-diagnostic-rendered-ansi 2>&1  | quiet --items 1
 ///
+/// Alternatively quiet can run Cargo for you with the right message format:
+/// quiet --items 1 run -- test
+///
 /// The following example scripts can be found in the repository:
 ///
 /// qcompile - Only compiles your production code
@@ -17,30 +22,180 @@ use clap::Parser;
 ///
 /// qrun-test - Runs your unit and integration tests
 ///
-/// Note: The above scripts use `watch`, which you can install with: `cargo install cargo-watch`. See https://crates.io/crates/cargo-watch for more information.
+/// The above scripts use quiet's watch mode, which re-runs Cargo when your files change:
+/// quiet --items 1 watch -- test
 ///
 /// Feel free to modify these scripts to suit your own needs.
+///
+/// Quiet exits with one of the following codes:
+///
+/// 0 - No compilation errors or test failures
+///
+/// 1 - Compilation errors (or warnings when using --fatal-warnings)
+///
+/// 2 - Test failures
+///
+/// 3 - Some of Cargo's output could not be decoded
+///
+/// 4 - Quiet itself failed, such as when its options are invalid or Cargo could not be run
+///
+/// Options can also be set in named profiles in a quiet.toml file, in the root of your project or in ~/.config/quiet. Options on the command line override those in the profile. For example:
+///
+/// [profiles.refactor]
+///
+/// items = 1
+///
+/// exclude_code = ["dead_code", "unused_*"]
 
 #[derive(Parser)]
 #[command(author, version, about)]
 #[derive(Debug, Clone)]
 pub struct Cli {
 
-  /// The number of items to show. Values range from 0 to 255. This includes errors and warnings to display. By default only errors are shown. Use --show-warnings to include warnings.
+  /// The number of items to show. This includes errors and warnings to display. By default only errors are shown. Use --show-warnings to include warnings. Required, unless --max-errors or --max-warnings are supplied or it is set in a profile.
   #[arg(long)]
-  pub items: u8,
+  pub items: Option<usize>,
+
+  /// The number of errors to show, independently of the number of warnings. When used with --items, whichever limit is reached first applies. Example: --max-errors 3
+  #[arg(long)]
+  pub max_errors: Option<usize>,
+
+  /// The number of warnings to show, independently of the number of errors. Implies --show-warnings. When used with --items, whichever limit is reached first applies. Example: --max-warnings 10
+  #[arg(long)]
+  pub max_warnings: Option<usize>,
+
+  /// The number of failing tests to show, with their captured output and panic message. The rest of the failing tests are counted instead of shown, so you can fix them one at a time. Only applies to text output. Example: --max-failures 1
+  #[arg(long)]
+  pub max_failures: Option<usize>,
+
+  /// The profile in quiet.toml to take options from. The profile named default is used if this is not supplied and it exists. Example: --profile refactor
+  #[arg(long)]
+  pub profile: Option<String>,
 
   /// Flag to include warnings in the output
   #[arg(long, default_value_t = false)]
   pub show_warnings: bool,
 
-  /// The file (if any) to filter on. Matches the file at the end of the path so you don't have to specify a full path. Example: --file-filter main.rs
-  #[arg(long)]
-  pub file_filter: Option<String>,
+  /// Flag to include notes, help and failure notes that are not attached to an error or warning, such as "For more information about this error, try `rustc --explain E0308`". These are shown after the errors and warnings, and are not limited by --items
+  #[arg(long, default_value_t = false)]
+  pub show_notes: bool,
+
+  /// The files (if any) to show messages for. Can be repeated. Plain names match the end of the path so you don't have to specify a full path. Patterns containing any of *?[{ are globs and patterns prefixed with regex: are regular expressions, both matched against the path relative to the workspace root. Example: --file-filter main.rs --file-filter 'src/process/**' --file-filter 'regex:_tests?\.rs$'
+  #[arg(long, value_parser = parse_file_pattern)]
+  pub file_filter: Vec<FilePattern>,
+
+  /// The files (if any) to hide messages for. Can be repeated. Takes the same patterns as --file-filter. Example: --exclude-file 'tests/**'
+  #[arg(long, value_parser = parse_file_pattern)]
+  pub exclude_file: Vec<FilePattern>,
+
+  /// Flag to only match --file-filter, --exclude-file and --lines against the primary span of each message; the location rustc points to as the cause. By default secondary spans, such as where a type is defined, are also matched
+  #[arg(long, default_value_t = false)]
+  pub primary_span_only: bool,
+
+  /// The lines (if any) to show messages for, in the files matched by --file-filter. Example: --file-filter main.rs --lines 120-200
+  #[arg(long, value_parser = parse_line_range)]
+  pub lines: Option<LineRange>,
+
+  /// Only show messages for these Cargo targets. Separate multiple targets with commas. Example: --target my_crate,integration_tests
+  #[arg(long, value_delimiter = ',')]
+  pub target: Vec<String>,
+
+  /// Only show messages for targets of these kinds. Separate multiple kinds with commas. Kinds include lib, bin, test, example, bench, proc-macro and custom-build. Example: --target-kind lib,bin
+  #[arg(long, value_delimiter = ',')]
+  pub target_kind: Vec<String>,
+
+  /// Only show messages with these error or lint codes. Separate multiple codes with commas. A trailing * matches any code starting with what comes before it. Example: --include-code E0308,clippy::needless_return
+  #[arg(long, value_delimiter = ',')]
+  pub include_code: Vec<String>,
+
+  /// Hide messages with these error or lint codes. Separate multiple codes with commas. A trailing * matches any code starting with what comes before it. Example: --exclude-code dead_code,unused_*
+  #[arg(long, value_delimiter = ',')]
+  pub exclude_code: Vec<String>,
+
+  /// The order to show messages in. Errors are always shown before warnings. Sorting waits for all of Cargo's output before showing any errors, so the limits apply to the sorted messages
+  #[arg(long, value_enum, default_value_t = SortOrder::Arrival)]
+  pub sort: SortOrder,
+
+  /// Flag to group messages under a header for each file, with the number of errors and warnings in the file. Messages are grouped by the file rustc points to as the cause, rather than the root of the target. Only applies to text output
+  #[arg(long, default_value_t = false)]
+  pub group_by_file: bool,
+
+  /// Flag to only show the number of errors and warnings in each file, instead of the messages themselves. --items is not required with this flag
+  #[arg(long, default_value_t = false, conflicts_with = "fix")]
+  pub summary_only: bool,
 
   /// Strings to filter out from the output. Note, this only applies to Strings quiet does not need to output. Example --filtered-out "libunwind: malformed"
   #[arg(long)]
-  pub filtered_out: Vec<String>
+  pub filtered_out: Vec<String>,
+
+  /// Flag to treat warnings as compilation errors when deciding on the exit code
+  #[arg(long, default_value_t = false)]
+  pub fatal_warnings: bool,
+
+  /// Flag to apply the machine applicable suggestions of the messages shown. Only the shown messages are fixed. A diff of the changes is shown first. Can only be used with text output
+  #[arg(long, default_value_t = false)]
+  pub fix: bool,
+
+  /// Flag to only show the diff of the changes --fix would make, without changing any files
+  #[arg(long, default_value_t = false, requires = "fix")]
+  pub dry_run: bool,
+
+  /// The format to output messages in. Use json or ndjson to consume quiet's output from other tools
+  #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+  pub output: OutputFormat,
+
+  #[command(subcommand)]
+  pub command: Option<Command>,
+
+}
+
+
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+  /// Coloured text for people to read
+  Text,
+  /// A single JSON document written once all of Cargo's output has been processed
+  Json,
+  /// A JSON object per line, written as soon as each item is available
+  Ndjson,
+}
+
+
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+  /// The order Cargo reports messages in. Errors are shown as soon as they arrive
+  Arrival,
+  /// By the file of the primary span, keeping the order Cargo reports messages in within each file
+  File,
+  /// By the file of the primary span, then from the top to the bottom of each file
+  Line,
+  /// By error or lint code, then by file and line
+  Code,
+}
+
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+
+  /// Run Cargo with the message format quiet needs and show its output. If quiet finds no problems, it exits with Cargo's exit code. Example: quiet --items 1 run -- test --no-run
+  Run {
+    /// The Cargo command to run, followed by its arguments. Example: check, test, clippy or build
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+    cargo_args: Vec<String>
+  },
+
+  /// Run Cargo every time a file in src, tests or Cargo.toml changes. A run in progress is cancelled when a new change arrives. Example: quiet --items 1 watch -- test
+  Watch {
+    /// The number of milliseconds to wait for changes to settle before running Cargo
+    #[arg(long, default_value_t = 300)]
+    debounce: u64,
+
+    /// The Cargo command to run, followed by its arguments. Example: check, test, clippy or build
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+    cargo_args: Vec<String>
+  },
 
 }
 
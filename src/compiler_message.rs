@@ -11,7 +11,17 @@ pub struct CompilerMessage {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CompilerMessageTarget {
   pub name: String,
-  pub src_path: String
+  pub src_path: String,
+  /// Example: lib, bin, test, example, bench, proc-macro or custom-build
+  #[serde(default)]
+  pub kind: Vec<String>,
+  /// Example: lib, rlib, cdylib or bin
+  #[serde(default)]
+  pub crate_types: Vec<String>,
+  pub edition: Option<String>,
+  pub doc: Option<bool>,
+  pub doctest: Option<bool>,
+  pub test: Option<bool>,
 }
 
 
@@ -19,22 +29,138 @@ pub struct CompilerMessageTarget {
 pub struct CompilerMessageMessage {
   pub rendered: String,
   pub code: Option<CompilerMessageCode>,
-  pub level: String,
+  pub level: Level,
   pub message: String,
-  pub spans: Vec<CompilerMessageSpan>
+  pub spans: Vec<CompilerMessageSpan>,
+  #[serde(default)]
+  pub children: Vec<CompilerMessageChild>,
+}
+
+
+/// Extra information attached to a message, such as notes and help with suggested fixes. Children
+/// are not rendered on their own, as they are included in the rendered output of their parent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CompilerMessageChild {
+  pub rendered: Option<String>,
+  pub code: Option<CompilerMessageCode>,
+  pub level: Level,
+  pub message: String,
+  pub spans: Vec<CompilerMessageSpan>,
+  #[serde(default)]
+  pub children: Vec<CompilerMessageChild>,
+}
+
+
+/// The levels rustc reports messages at.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Level {
+  /// The compiler crashed. This is a bug in rustc rather than in the code being compiled
+  InternalCompilerError,
+  Error,
+  Warning,
+  Note,
+  Help,
+  /// Extra information about a failure, such as "For more information about this error, try `rustc --explain E0308`"
+  FailureNote,
+  /// A level added to rustc after this was written
+  Other(String),
+}
+
+impl From<String> for Level {
+  fn from(level: String) -> Self {
+    match level.as_str() {
+      "error: internal compiler error" => Level::InternalCompilerError,
+      "error"                          => Level::Error,
+      "warning"                        => Level::Warning,
+      "note"                           => Level::Note,
+      "help"                           => Level::Help,
+      "failure-note"                   => Level::FailureNote,
+      _                                => Level::Other(level),
+    }
+  }
+}
+
+impl From<Level> for String {
+  fn from(level: Level) -> Self {
+    level.as_str().to_owned()
+  }
+}
+
+impl Level {
+  /// The level as rustc reports it.
+  pub fn as_str(&self) -> &str {
+    match self {
+      Level::InternalCompilerError => "error: internal compiler error",
+      Level::Error                 => "error",
+      Level::Warning               => "warning",
+      Level::Note                  => "note",
+      Level::Help                  => "help",
+      Level::FailureNote           => "failure-note",
+      Level::Other(level)          => level,
+    }
+  }
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CompilerMessageCode {
-  code: String,
-  explanation: Option<String>
+  pub code: String,
+  pub explanation: Option<String>
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CompilerMessageSpan {
   pub file_name: String,
-  is_primary: bool,
-  label: Option<String>,
+  pub byte_start: u32,
+  pub byte_end: u32,
+  /// Lines are 1-based and inclusive
+  pub line_start: usize,
+  pub line_end: usize,
+  /// Columns are 1-based and exclusive of column_end
+  pub column_start: usize,
+  pub column_end: usize,
+  pub is_primary: bool,
+  /// The source lines covered by this span
+  #[serde(default)]
+  pub text: Vec<CompilerMessageSpanText>,
+  pub label: Option<String>,
+  pub suggested_replacement: Option<String>,
+  pub suggestion_applicability: Option<Applicability>,
+  /// Set when the span is inside a macro expansion
+  pub expansion: Option<Box<CompilerMessageSpanExpansion>>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CompilerMessageSpanText {
+  pub text: String,
+  /// The 1-based columns of the highlighted part of the line
+  pub highlight_start: usize,
+  pub highlight_end: usize,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CompilerMessageSpanExpansion {
+  /// The span of the macro invocation
+  pub span: CompilerMessageSpan,
+  /// Example: vec! or #[derive(Debug)]
+  pub macro_decl_name: String,
+  /// Where the macro was defined, if known
+  pub def_site_span: Option<CompilerMessageSpan>,
+}
+
+
+/// How confident rustc is that a suggested replacement is correct.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Applicability {
+  /// The suggestion is definitely what the user intended and can be applied automatically
+  MachineApplicable,
+  /// The suggestion may be what the user intended, but it is uncertain
+  MaybeIncorrect,
+  /// The suggestion contains placeholders like `(...)` or `{ /* fields */ }`
+  HasPlaceholders,
+  Unspecified,
 }
@@ -1,7 +1,11 @@
 use crate::CompilerMessage;
+use crate::cargo_event::CargoEvent;
+use crate::test_event::TestEvent;
 
 pub enum CompilerMessageDecodingStatus {
-  DecodedCompilerMessage(CompilerMessage),
+  DecodedCompilerMessage(Box<CompilerMessage>),
+  DecodedCargoEvent(CargoEvent),
+  DecodedTestEvent(TestEvent),
   StdOutLine(String),
   Ignore
 }
@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+use std::format as s;
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use serde::Deserialize;
+use toml::Table;
+
+use crate::cli::{Cli, OutputFormat, SortOrder};
+use crate::process::outcome::QUIET_ERROR_EXIT_CODE;
+use crate::file_pattern::{parse_file_pattern, parse_line_range};
+use crate::workspace::find_workspace_root;
+
+const CONFIG_FILE_NAME: &str = "quiet.toml";
+
+/// The profile used when --profile is not supplied, if it exists.
+const DEFAULT_PROFILE: &str = "default";
+
+
+/// A named set of options. Each option has the same name as its command line flag, with hyphens
+/// replaced by underscores.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+  items: Option<usize>,
+  max_errors: Option<usize>,
+  max_warnings: Option<usize>,
+  max_failures: Option<usize>,
+  show_warnings: Option<bool>,
+  show_notes: Option<bool>,
+  file_filter: Option<Vec<String>>,
+  exclude_file: Option<Vec<String>>,
+  primary_span_only: Option<bool>,
+  lines: Option<String>,
+  target: Option<Vec<String>>,
+  target_kind: Option<Vec<String>>,
+  include_code: Option<Vec<String>>,
+  exclude_code: Option<Vec<String>>,
+  sort: Option<SortOrder>,
+  group_by_file: Option<bool>,
+  summary_only: Option<bool>,
+  filtered_out: Option<Vec<String>>,
+  fatal_warnings: Option<bool>,
+  output: Option<OutputFormat>,
+}
+
+
+/// Parses the command line, filling in any options not supplied from the selected profile.
+/// Exits with an error if the profile can't be loaded, no limit on the number of items has been set or
+/// --fix is used with an output other than text.
+pub fn parse_cli() -> Cli {
+  let matches = Cli::command().try_get_matches().unwrap_or_else(|e| exit_with(e));
+  let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| exit_with(e));
+
+  if let Err(e) = apply_profile(&mut cli, &matches) {
+    exit_with(Cli::command().error(ErrorKind::InvalidValue, e))
+  }
+
+  // The limits don't matter when only the number of messages in each file is shown
+  if cli.items.is_none() && cli.max_errors.is_none() && cli.max_warnings.is_none() && !cli.summary_only {
+    exit_with(
+      Cli::command()
+        .error(ErrorKind::MissingRequiredArgument, s!("--items, --max-errors or --max-warnings is required, either on the command line or in a profile in {}", CONFIG_FILE_NAME))
+    )
+  }
+
+  // The diff of the fixes is text, which would break the JSON documents written to stdout
+  if cli.fix && cli.output != OutputFormat::Text {
+    exit_with(Cli::command().error(ErrorKind::ArgumentConflict, "--fix can only be used with text output"))
+  }
+
+  cli
+}
+
+
+/// Errors exit with quiet's own exit code, rather than clap's, so they can't be mistaken for the
+/// outcome of a build. Help and version information still exit successfully.
+fn exit_with(error: clap::Error) -> ! {
+  if !error.use_stderr() {
+    error.exit()
+  }
+
+  let _ = error.print();
+  std::process::exit(i32::from(QUIET_ERROR_EXIT_CODE))
+}
+
+
+fn apply_profile(cli: &mut Cli, matches: &ArgMatches) -> Result<(), String> {
+  let profile_name = cli.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+
+  let maybe_profile = load_profile(profile_name, cli.profile.is_some())?;
+
+  let profile =
+    match (maybe_profile, &cli.profile) {
+      (Some(profile), _) => profile,
+      (None, None) => return Ok(()),
+      (None, Some(_)) => return Err(s!("Could not find profile '{}' in any {}", profile_name, CONFIG_FILE_NAME)),
+    };
+
+  // Options supplied on the command line override those in the profile
+  let from_profile = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+  let parse_patterns = |patterns: Vec<String>| {
+    patterns
+      .iter()
+      .map(|pattern| parse_file_pattern(pattern))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|e| s!("Invalid file pattern in profile '{}': {}", profile_name, e))
+  };
+
+  if let Some(items) = profile.items.filter(|_| from_profile("items")) {
+    cli.items = Some(items)
+  }
+
+  if let Some(max_errors) = profile.max_errors.filter(|_| from_profile("max_errors")) {
+    cli.max_errors = Some(max_errors)
+  }
+
+  if let Some(max_warnings) = profile.max_warnings.filter(|_| from_profile("max_warnings")) {
+    cli.max_warnings = Some(max_warnings)
+  }
+
+  if let Some(max_failures) = profile.max_failures.filter(|_| from_profile("max_failures")) {
+    cli.max_failures = Some(max_failures)
+  }
+
+  if let Some(show_warnings) = profile.show_warnings.filter(|_| from_profile("show_warnings")) {
+    cli.show_warnings = show_warnings
+  }
+
+  if let Some(show_notes) = profile.show_notes.filter(|_| from_profile("show_notes")) {
+    cli.show_notes = show_notes
+  }
+
+  if let Some(file_filter) = profile.file_filter.filter(|_| from_profile("file_filter")) {
+    cli.file_filter = parse_patterns(file_filter)?
+  }
+
+  if let Some(exclude_file) = profile.exclude_file.filter(|_| from_profile("exclude_file")) {
+    cli.exclude_file = parse_patterns(exclude_file)?
+  }
+
+  if let Some(primary_span_only) = profile.primary_span_only.filter(|_| from_profile("primary_span_only")) {
+    cli.primary_span_only = primary_span_only
+  }
+
+  if let Some(lines) = profile.lines.filter(|_| from_profile("lines")) {
+    let line_range =
+      parse_line_range(&lines)
+        .map_err(|e| s!("Invalid lines in profile '{}': {}", profile_name, e))?;
+
+    cli.lines = Some(line_range)
+  }
+
+  if let Some(target) = profile.target.filter(|_| from_profile("target")) {
+    cli.target = target
+  }
+
+  if let Some(target_kind) = profile.target_kind.filter(|_| from_profile("target_kind")) {
+    cli.target_kind = target_kind
+  }
+
+  if let Some(include_code) = profile.include_code.filter(|_| from_profile("include_code")) {
+    cli.include_code = include_code
+  }
+
+  if let Some(exclude_code) = profile.exclude_code.filter(|_| from_profile("exclude_code")) {
+    cli.exclude_code = exclude_code
+  }
+
+  if let Some(sort) = profile.sort.filter(|_| from_profile("sort")) {
+    cli.sort = sort
+  }
+
+  if let Some(group_by_file) = profile.group_by_file.filter(|_| from_profile("group_by_file")) {
+    cli.group_by_file = group_by_file
+  }
+
+  if let Some(summary_only) = profile.summary_only.filter(|_| from_profile("summary_only")) {
+    cli.summary_only = summary_only
+  }
+
+  if let Some(filtered_out) = profile.filtered_out.filter(|_| from_profile("filtered_out")) {
+    cli.filtered_out = filtered_out
+  }
+
+  if let Some(fatal_warnings) = profile.fatal_warnings.filter(|_| from_profile("fatal_warnings")) {
+    cli.fatal_warnings = fatal_warnings
+  }
+
+  if let Some(output) = profile.output.filter(|_| from_profile("output")) {
+    cli.output = output
+  }
+
+  Ok(())
+}
+
+
+/// Loads the profile from the first config file that has it. Only the profile itself is checked for
+/// unknown options, so a mistake in another profile doesn't stop quiet from running. Config files
+/// that can't be read are skipped, unless the profile was asked for by name.
+fn load_profile(profile_name: &str, named: bool) -> Result<Option<Profile>, String> {
+  for path in config_file_paths().into_iter().filter(|path| path.is_file()) {
+    let config =
+      std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| content.parse::<Table>().map_err(|e| e.to_string()));
+
+    let config =
+      match config {
+        Ok(config) => config,
+        Err(e) if named => return Err(s!("Could not load {}: {}", path.to_string_lossy(), e)),
+        Err(_) => continue,
+      };
+
+    let maybe_profile =
+      config
+        .get("profiles")
+        .and_then(|profiles| profiles.get(profile_name));
+
+    if let Some(profile) = maybe_profile {
+      return
+        profile
+          .clone()
+          .try_into()
+          .map(Some)
+          .map_err(|e| s!("Could not load profile '{}' from {}: {}", profile_name, path.to_string_lossy(), e))
+    }
+  }
+
+  Ok(None)
+}
+
+
+/// The project's config file takes precedence over the user's config file.
+fn config_file_paths() -> Vec<PathBuf> {
+  let project_directory =
+    find_workspace_root()
+      .or_else(|| std::env::current_dir().ok());
+
+  let user_config_directory =
+    std::env::var_os("XDG_CONFIG_HOME")
+      .map(PathBuf::from)
+      .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+  project_directory
+    .into_iter()
+    .chain(user_config_directory.map(|directory| directory.join("quiet")))
+    .map(|directory| directory.join(CONFIG_FILE_NAME))
+    .collect()
+}
@@ -0,0 +1,20 @@
+use serde::Serialize;
+
+/// A JSON line from Cargo that could not be decoded.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodingError {
+  /// What we were trying to decode the line as. Example: CompilerMessage
+  pub decoding: &'static str,
+  pub line: String,
+  pub cause: String,
+}
+
+impl DecodingError {
+  pub fn new(decoding: &'static str, line: &str, cause: serde_json::Error) -> Self {
+    Self {
+      decoding,
+      line: line.to_owned(),
+      cause: cause.to_string(),
+    }
+  }
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+use std::format as s;
+
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+
+const REGEX_PREFIX: &str = "regex:";
+
+
+/// A pattern that file names from compiler messages are matched against.
+#[derive(Debug, Clone)]
+pub enum FilePattern {
+  /// Matches a file name ending with this path. Example: main.rs or process/mod.rs
+  Suffix(String),
+  /// Matches the whole file name, relative to the workspace root. Example: src/process/*.rs
+  Glob(GlobMatcher),
+  /// Matches anywhere in the file name, relative to the workspace root. Example: regex:_(test|spec)\.rs$
+  Regex(Regex),
+}
+
+impl FilePattern {
+
+  pub fn matches(&self, file_name: &str) -> bool {
+    match self {
+      FilePattern::Suffix(suffix) => {
+        // Only match whole path components, so main.rs doesn't match xmain.rs
+        file_name == suffix || file_name.ends_with(&s!("/{}", suffix))
+      },
+      FilePattern::Glob(glob) => glob.is_match(file_name),
+      FilePattern::Regex(regex) => regex.is_match(file_name),
+    }
+  }
+}
+
+
+/// Parses a pattern supplied on the command line. Patterns prefixed with `regex:` are regular
+/// expressions, patterns containing any of `*?[{` are globs and anything else is a suffix.
+pub fn parse_file_pattern(pattern: &str) -> Result<FilePattern, String> {
+  if let Some(regex) = pattern.strip_prefix(REGEX_PREFIX) {
+    Regex::new(regex)
+      .map(FilePattern::Regex)
+      .map_err(|e| s!("Invalid regex: {}", e))
+  } else if pattern.contains(['*', '?', '[', '{']) {
+    GlobBuilder::new(pattern)
+      .literal_separator(true)
+      .build()
+      .map(|glob| FilePattern::Glob(glob.compile_matcher()))
+      .map_err(|e| s!("Invalid glob: {}", e))
+  } else {
+    Ok(FilePattern::Suffix(normalise_separators(pattern.trim_start_matches("./"))))
+  }
+}
+
+
+/// An inclusive range of 1-based line numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+  pub start: usize,
+  pub end: usize,
+}
+
+impl LineRange {
+  pub fn overlaps(&self, line_start: usize, line_end: usize) -> bool {
+    line_start <= self.end && line_end >= self.start
+  }
+}
+
+
+/// Parses a range of lines supplied on the command line. Example: 120-200 or 120 for a single line
+pub fn parse_line_range(range: &str) -> Result<LineRange, String> {
+  let parse_line = |line: &str| {
+    line
+      .trim()
+      .parse::<usize>()
+      .map_err(|e| s!("Invalid line number '{}': {}", line, e))
+  };
+
+  let (start, end) =
+    match range.split_once('-') {
+      Some((start, end)) => (parse_line(start)?, parse_line(end)?),
+      None => {
+        let line = parse_line(range)?;
+        (line, line)
+      }
+    };
+
+  if start > end {
+    Err(s!("The start of the range ({}) is after the end ({})", start, end))
+  } else {
+    Ok(LineRange { start, end })
+  }
+}
+
+
+/// Rustc reports file names relative to the workspace root for workspace members and as absolute
+/// paths for everything else. Makes absolute paths within the workspace relative to its root so
+/// that all file names can be matched the same way.
+pub fn normalise_file_name(file_name: &str, workspace_root: Option<&Path>) -> String {
+  let path = Path::new(file_name);
+  let relative =
+    workspace_root
+      .and_then(|root| path.strip_prefix(root).ok())
+      .unwrap_or(path);
+
+  normalise_separators(relative.to_string_lossy().trim_start_matches("./"))
+}
+
+
+fn normalise_separators(file_name: &str) -> String {
+  file_name.replace('\\', "/")
+}
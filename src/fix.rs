@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::format as s;
+
+use ansi_term::Color::{Red, Green, Blue, Yellow};
+use similar::{ChangeTag, TextDiff};
+
+use crate::CompilerMessage;
+use crate::compiler_message::{Applicability, CompilerMessageChild, CompilerMessageSpan};
+use crate::workspace::find_workspace_root;
+
+
+/// A single suggested replacement of a byte range within a file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Replacement {
+  byte_start: usize,
+  byte_end: usize,
+  replacement: String,
+}
+
+
+/// The changes to a single file.
+struct FileFix {
+  path: PathBuf,
+  original: String,
+  fixed: String,
+  replacements: usize,
+}
+
+
+/// Applies the machine applicable suggestions from the supplied messages only, so the rest of the
+/// crate is left untouched. A diff of each file is shown before it is changed. With dry_run, only
+/// the diff is shown.
+pub fn apply_fixes(compiler_messages: &[CompilerMessage], dry_run: bool) {
+  println!();
+
+  let replacements_by_file = get_replacements(compiler_messages);
+  if replacements_by_file.is_empty() {
+    println!("{}", Yellow.paint("*** No machine applicable fixes found ***"));
+    return
+  }
+
+  let mut fixed_files = 0;
+  let mut fixed_replacements = 0;
+
+  for (file_name, replacements) in replacements_by_file {
+    match get_file_fix(&file_name, replacements) {
+      Ok(file_fix) => {
+        print_diff(&file_name, &file_fix);
+
+        if !dry_run {
+          if let Err(e) = std::fs::write(&file_fix.path, &file_fix.fixed) {
+            println!("{}", Red.paint(s!("Could not write fixes to {}: {}", file_fix.path.to_string_lossy(), e)));
+            continue
+          }
+        }
+
+        fixed_files += 1;
+        fixed_replacements += file_fix.replacements;
+      },
+      Err(e) => println!("{}", Red.paint(e)),
+    }
+  }
+
+  let summary =
+    if dry_run {
+      s!("*** Dry run: {} fix(es) to {} file(s) not applied ***", fixed_replacements, fixed_files)
+    } else {
+      s!("*** Applied {} fix(es) to {} file(s) ***", fixed_replacements, fixed_files)
+    };
+
+  println!("\n{}", Green.paint(summary));
+}
+
+
+fn get_replacements(compiler_messages: &[CompilerMessage]) -> BTreeMap<String, Vec<Replacement>> {
+  let mut replacements_by_file: BTreeMap<String, Vec<Replacement>> = BTreeMap::new();
+
+  compiler_messages
+    .iter()
+    .flat_map(|cm| {
+      cm
+        .message
+        .spans
+        .iter()
+        .chain(child_spans(&cm.message.children))
+    })
+    .filter_map(|span| machine_applicable_replacement(span).map(|r| (span.file_name.clone(), r)))
+    .for_each(|(file_name, replacement)| {
+      replacements_by_file
+        .entry(file_name)
+        .or_default()
+        .push(replacement)
+    });
+
+  replacements_by_file
+}
+
+
+fn child_spans(children: &[CompilerMessageChild]) -> Box<dyn Iterator<Item = &CompilerMessageSpan> + '_> {
+  Box::new(
+    children
+      .iter()
+      .flat_map(|child| child.spans.iter().chain(child_spans(&child.children)))
+  )
+}
+
+
+fn machine_applicable_replacement(span: &CompilerMessageSpan) -> Option<Replacement> {
+  match (&span.suggested_replacement, span.suggestion_applicability) {
+    (Some(replacement), Some(Applicability::MachineApplicable)) => {
+      Some(
+        Replacement {
+          byte_start: span.byte_start as usize,
+          byte_end: span.byte_end as usize,
+          replacement: replacement.to_owned(),
+        }
+      )
+    },
+    _ => None
+  }
+}
+
+
+fn get_file_fix(file_name: &str, mut replacements: Vec<Replacement>) -> Result<FileFix, String> {
+  let path =
+    find_file(file_name)
+      .ok_or_else(|| s!("Could not find {} to apply fixes to", file_name))?;
+
+  let original =
+    std::fs::read_to_string(&path)
+      .map_err(|e| s!("Could not read {}: {}", path.to_string_lossy(), e))?;
+
+  // The same suggestion can appear more than once, such as when a file is compiled for both a
+  // library and its tests.
+  replacements.sort();
+  replacements.dedup();
+
+  // Replacements that overlap an earlier one can't both be applied, so skip them.
+  let mut fixed = String::with_capacity(original.len());
+  let mut position = 0;
+  let mut applied = 0;
+
+  for replacement in replacements {
+    if replacement.byte_start < position {
+      continue
+    }
+
+    if original.get(replacement.byte_start..replacement.byte_end).is_none() {
+      return Err(s!("Suggested fix is outside {}. Has it changed since it was compiled?", file_name))
+    }
+
+    fixed.push_str(&original[position..replacement.byte_start]);
+    fixed.push_str(&replacement.replacement);
+    position = replacement.byte_end;
+    applied += 1;
+  }
+
+  fixed.push_str(&original[position..]);
+
+  Ok(
+    FileFix {
+      path,
+      original,
+      fixed,
+      replacements: applied,
+    }
+  )
+}
+
+
+/// File names are relative to the workspace root, which may be above the current directory.
+fn find_file(file_name: &str) -> Option<PathBuf> {
+  let file = Path::new(file_name);
+  let path =
+    if file.is_absolute() {
+      file.to_path_buf()
+    } else {
+      find_workspace_root()?.join(file)
+    };
+
+  Some(path).filter(|path| path.is_file())
+}
+
+
+fn print_diff(file_name: &str, file_fix: &FileFix) {
+  let diff = TextDiff::from_lines(&file_fix.original, &file_fix.fixed);
+
+  println!("{}", Blue.paint(s!("--- a/{}", file_name)));
+  println!("{}", Blue.paint(s!("+++ b/{}", file_name)));
+
+  diff
+    .unified_diff()
+    .iter_hunks()
+    .for_each(|hunk| {
+      println!("{}", Blue.paint(hunk.header().to_string()));
+      hunk
+        .iter_changes()
+        .for_each(|change| {
+          let line = s!("{}{}", change.tag(), change.to_string_lossy());
+          let line = line.strip_suffix('\n').unwrap_or(&line);
+          match change.tag() {
+            ChangeTag::Delete => println!("{}", Red.paint(line)),
+            ChangeTag::Insert => println!("{}", Green.paint(line)),
+            ChangeTag::Equal  => println!("{}", line),
+          }
+        })
+    });
+}
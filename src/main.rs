@@ -1,56 +1,71 @@
-use clap::Parser;
-use serde_json::Result as JsonResult;
+use std::process::ExitCode;
+use std::time::Duration;
 
-use cli::Cli;
+use cli::{Command, OutputFormat};
+use config::parse_cli;
 use compiler_message::CompilerMessage;
-use process::compiler_messages::{ItemTypes, get_matches};
-use process::stdout::{print_start_banner, print_compiler_output, print_errors, print_stdout_lines};
-use process::level_status::{by_level, LevelInfo};
-use process::limit::by_number;
-use process::filter::by_filename;
-use process::all_messages::AllMessages;
-use rendered::Rendered;
+use process::compiler_messages::get_matches;
+use process::outcome::QUIET_ERROR_EXIT_CODE;
+use process::stdout::print_start_banner;
+use process::pipeline::run_pipeline;
+use runner::run_cargo;
+use watch::watch;
 
 mod reason;
 mod cli;
+mod config;
 mod compiler_message;
+mod cargo_event;
 mod compiler_message_decoding_status;
-mod rendered;
+mod decoding_error;
+mod fix;
+mod file_pattern;
 mod process;
+mod runner;
+mod test_event;
+mod watch;
+mod workspace;
 
 
-fn main() -> JsonResult<()>{
-  let cli = Cli::parse();
-  let items_to_show = cli.items as usize;
-  let file_to_show_errors_for = cli.file_filter;
-  let show_warnings = cli.show_warnings;
-  let filtered_out = cli.filtered_out;
-  print_start_banner();
+fn main() -> ExitCode {
+  let cli = parse_cli();
 
-  let all_messages = get_all_messages();
-  let compiler_messages: Vec<CompilerMessage> = all_messages.compiler_messages;
-  let filtered_by_filename: Vec<CompilerMessage> = by_filename(file_to_show_errors_for, compiler_messages);
-  let level_info: LevelInfo = by_level(filtered_by_filename);
-  let limited_by_item_size: Vec<CompilerMessage> =
-    by_number(level_info.level_types, items_to_show, show_warnings);
-
-  print_stdout_lines(all_messages.stdout_lines, filtered_out);
-  print_errors(all_messages.errors);
-  print_compiler_output(limited_by_item_size, level_info.status);
-
-  Ok(())
+  match &cli.command {
+    Some(Command::Watch { debounce, cargo_args }) => {
+      // Only returns if watching fails
+      let result = watch(&cli, cargo_args, Duration::from_millis(*debounce));
+      if let Err(e) = result {
+        eprintln!("{}", e);
+      }
+      ExitCode::from(QUIET_ERROR_EXIT_CODE)
+    },
+    Some(Command::Run { cargo_args }) => {
+      if cli.output == OutputFormat::Text {
+        print_start_banner();
+      }
+      match run_cargo(&cli, cargo_args) {
+        Ok((outcome, _)) if !outcome.is_success() => outcome.exit_code(),
+        // Cargo can fail for reasons quiet doesn't know about, such as a missing manifest
+        Ok((_, status)) => cargo_exit_code(status.code()),
+        Err(e) => {
+          eprintln!("{}", e);
+          ExitCode::from(QUIET_ERROR_EXIT_CODE)
+        }
+      }
+    },
+    None => {
+      if cli.output == OutputFormat::Text {
+        print_start_banner();
+      }
+      run_pipeline(&cli, get_matches()).exit_code()
+    }
+  }
 }
 
 
-fn get_all_messages() -> AllMessages {
-  get_matches()
-    .into_iter()
-    .fold(AllMessages::new(), |mut acc: AllMessages, it| {
-      match it {
-        ItemTypes::CompilerMessageType(cm) => { acc.add_compiler_message(cm); acc },
-        ItemTypes::StdoutLineType(line)    => { acc.add_stdout_line(line); acc },
-        ItemTypes::ErrorType(error)        => { acc.add_error(error); acc },
-      }
-    })
+/// Cargo can be terminated by a signal, in which case it has no exit code.
+fn cargo_exit_code(code: Option<i32>) -> ExitCode {
+  code
+    .and_then(|c| u8::try_from(c).ok())
+    .map_or(ExitCode::FAILURE, ExitCode::from)
 }
-
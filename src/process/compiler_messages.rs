@@ -1,77 +1,137 @@
 use std::io::{stdin, BufRead};
 use serde_json;
 use crate::compiler_message::CompilerMessage;
+use crate::cargo_event::{BuildFinished, BuildScriptExecuted, BuildScriptFailed, CargoEvent, CompilerArtifact, CouldNotCompile};
 use crate::reason::Reason;
+use crate::test_event::{TestEvent, TestEventType};
 use crate::compiler_message_decoding_status::CompilerMessageDecodingStatus;
-use std::format as s;
-use ansi_term::Color::Red;
+use crate::decoding_error::DecodingError;
 
 
 #[allow(clippy::enum_variant_names)]
 pub enum ItemTypes {
-  CompilerMessageType(CompilerMessage),
+  CompilerMessageType(Box<CompilerMessage>),
+  CargoEventType(CargoEvent),
+  TestEventType(TestEvent),
   StdoutLineType(String),
-  ErrorType(String)
+  ErrorType(DecodingError)
 }
 
 
-pub fn get_matches() -> Vec<ItemTypes> {
-  get_compiler_messages()
-  .into_iter()
+/// Lazily decodes each line from stdin as it arrives, so callers can act on a message without
+/// waiting for Cargo to finish.
+pub fn get_matches() -> impl Iterator<Item = ItemTypes> {
+  get_matches_from(lossy_lines(stdin().lock()))
+}
+
+
+/// Reads lines until the end of the source, replacing any bytes that are not valid UTF-8 rather
+/// than stopping at them. Test binaries can print anything.
+pub fn lossy_lines<R: BufRead>(mut source: R) -> impl Iterator<Item = String> {
+  std::iter::from_fn(move || {
+    let mut buffer = vec![];
+    match source.read_until(b'\n', &mut buffer) {
+      Ok(0) | Err(_) => None,
+      Ok(_) => {
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        Some(line.strip_suffix('\r').unwrap_or(line).to_owned())
+      },
+    }
+  })
+}
+
+
+/// Lazily decodes each line from the supplied source, such as the output of a Cargo process.
+pub fn get_matches_from<I>(lines: I) -> impl Iterator<Item = ItemTypes>
+  where I: Iterator<Item = String>
+{
+  get_compiler_messages(lines)
   .filter_map(|r| {
     match r {
       Ok(CompilerMessageDecodingStatus::DecodedCompilerMessage(cm)) => Some(ItemTypes::CompilerMessageType(cm)),
+      Ok(CompilerMessageDecodingStatus::DecodedCargoEvent(event)) => Some(ItemTypes::CargoEventType(event)),
+      Ok(CompilerMessageDecodingStatus::DecodedTestEvent(event)) => Some(ItemTypes::TestEventType(event)),
       Ok(CompilerMessageDecodingStatus::StdOutLine(line)) => {
         Some(ItemTypes::StdoutLineType(line))
       },
       Ok(CompilerMessageDecodingStatus::Ignore) => None,
       Err(e) => {
-        Some(ItemTypes::ErrorType(e.to_string()))
+        Some(ItemTypes::ErrorType(e))
       },
     }
   })
-  .collect()
 }
 
 
-fn get_compiler_messages() -> Vec<Result<CompilerMessageDecodingStatus, String>> {
-  stdin()
-  .lock()
-  .lines()
-  .map(|line_result|{
-    let line = line_result.unwrap();
+fn get_compiler_messages<I>(lines: I) -> impl Iterator<Item = Result<CompilerMessageDecodingStatus, DecodingError>>
+  where I: Iterator<Item = String>
+{
+  lines
+  .map(|line|{
     // if it's not a JSON payload
     if !&line.starts_with('{') {
-      Ok(CompilerMessageDecodingStatus::StdOutLine(line))
-    } else {
-      let process_result: Result<CompilerMessageDecodingStatus, String> =
-        process_compiler_message(line.as_str())
-          .map(|maybe_cm| {
-            maybe_cm.map_or_else(|| CompilerMessageDecodingStatus::Ignore, CompilerMessageDecodingStatus::DecodedCompilerMessage)
-          });
+      let maybe_cargo_event =
+        BuildScriptFailed::from_line(&line)
+          .map(CargoEvent::BuildScriptFailed)
+          .or_else(|| CouldNotCompile::from_line(&line).map(CargoEvent::CouldNotCompile));
 
-      process_result
+      match maybe_cargo_event {
+        Some(cargo_event) => Ok(CompilerMessageDecodingStatus::DecodedCargoEvent(cargo_event)),
+        None => Ok(CompilerMessageDecodingStatus::StdOutLine(line)),
+      }
+    } else {
+      process_json_line(line.as_str())
     }
   })
-  .collect()
 }
 
 
-fn process_compiler_message(line: &str) -> Result<Option<CompilerMessage>, String> {
+fn process_json_line(line: &str) -> Result<CompilerMessageDecodingStatus, DecodingError> {
+  // Tests run with --format json report their results as JSON on the same stream as Cargo
+  if let Ok(test_event_type) = serde_json::from_str::<TestEventType>(line) {
+    return process_test_event(line, test_event_type)
+  }
+
   let reason =
     decode_reason(line)
-      .map_err(|e| {
-          s!("******************* Failed to decode Reason from this line: {}\ncause: {}", Red.paint(line), e)
-        })?;
-
-  if reason.reason == "compiler-message" {
-    decode_compiler_message(line)
-      .map(Some)
-      .map_err(|e| {
-        s!("******************* Failed to decode CompilerMessage from this line: {}\ncause: {}", Red.paint(line), e)
-      })
-  } else {
-    Ok(None)
+      .map_err(|e| DecodingError::new("Reason", line, e))?;
+
+  match reason.reason.as_str() {
+    "compiler-message" => {
+      decode_compiler_message(line)
+        .map(CompilerMessageDecodingStatus::DecodedCompilerMessage)
+        .map_err(|e| DecodingError::new("CompilerMessage", line, e))
+    },
+    "compiler-artifact" => {
+      serde_json::from_str::<CompilerArtifact>(line)
+        .map(|artifact| CompilerMessageDecodingStatus::DecodedCargoEvent(CargoEvent::CompilerArtifact(Box::new(artifact))))
+        .map_err(|e| DecodingError::new("CompilerArtifact", line, e))
+    },
+    "build-script-executed" => {
+      serde_json::from_str::<BuildScriptExecuted>(line)
+        .map(|executed| CompilerMessageDecodingStatus::DecodedCargoEvent(CargoEvent::BuildScriptExecuted(executed)))
+        .map_err(|e| DecodingError::new("BuildScriptExecuted", line, e))
+    },
+    "build-finished" => {
+      serde_json::from_str::<BuildFinished>(line)
+        .map(|finished| CompilerMessageDecodingStatus::DecodedCargoEvent(CargoEvent::BuildFinished(finished)))
+        .map_err(|e| DecodingError::new("BuildFinished", line, e))
+    },
+    _ => Ok(CompilerMessageDecodingStatus::Ignore),
+  }
+}
+
+
+fn process_test_event(line: &str, test_event_type: TestEventType) -> Result<CompilerMessageDecodingStatus, DecodingError> {
+  match test_event_type.event_type.as_str() {
+    "suite" | "test" => {
+      serde_json::from_str::<TestEvent>(line)
+        .map(CompilerMessageDecodingStatus::DecodedTestEvent)
+        .map_err(|e| DecodingError::new("TestEvent", line, e))
+    },
+    // Such as benchmarks
+    _ => Ok(CompilerMessageDecodingStatus::Ignore),
   }
 }
 
@@ -81,6 +141,6 @@ fn decode_reason(line: &str) -> serde_json::Result<Reason> {
 }
 
 
-fn decode_compiler_message(line: &str) -> serde_json::Result<CompilerMessage> {
+fn decode_compiler_message(line: &str) -> serde_json::Result<Box<CompilerMessage>> {
   serde_json::from_str(line)
 }
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::CompilerMessage;
+use crate::compiler_message::Level;
+
+
+/// What makes two messages the same, regardless of the target they were reported for. Rustc
+/// reports the same problem once per target that compiles the code, such as the lib and its unit
+/// tests, and the rendered output can differ between them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct DedupKey {
+  pub level: Level,
+  pub code: Option<String>,
+  pub message: String,
+  pub file_name: Option<String>,
+  pub line: Option<usize>,
+  pub column: Option<usize>,
+}
+
+impl DedupKey {
+
+  pub fn new(compiler_message: &CompilerMessage) -> Self {
+    let message = &compiler_message.message;
+    let primary_span =
+      message
+        .spans
+        .iter()
+        .find(|span| span.is_primary);
+
+    Self {
+      level: message.level.clone(),
+      code: message.code.as_ref().map(|code| code.code.clone()),
+      message: message.message.clone(),
+      file_name: primary_span.map(|span| span.file_name.clone()),
+      line: primary_span.map(|span| span.line_start),
+      column: primary_span.map(|span| span.column_start),
+    }
+  }
+}
+
+
+/// A message that was reported for more targets after it was shown.
+#[derive(Debug, Clone, Serialize)]
+pub struct Repeated {
+  #[serde(flatten)]
+  pub key: DedupKey,
+  pub targets: usize,
+}
+
+
+#[derive(Debug, Default)]
+struct Occurrences {
+  /// Cargo reports a lib and its unit tests with the same target name and kind, so each time the
+  /// message is reported is counted as a target
+  targets: usize,
+  /// The number of targets when the message was shown, if it has been
+  targets_when_shown: Option<usize>,
+}
+
+
+/// Removes messages that have already been seen, while counting the targets each was reported for.
+#[derive(Debug, Default)]
+pub struct Dedup {
+  seen: HashMap<DedupKey, Occurrences>,
+}
+
+impl Dedup {
+
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Returns the message if it is the first of its kind.
+  pub fn by_duplicate(&mut self, compiler_message: CompilerMessage) -> Option<CompilerMessage> {
+    let occurrences = self.seen.entry(DedupKey::new(&compiler_message)).or_default();
+    occurrences.targets += 1;
+
+    (occurrences.targets == 1).then_some(compiler_message)
+  }
+
+  /// Records that the message is being shown and returns the number of targets it has been
+  /// reported for so far.
+  pub fn shown(&mut self, compiler_message: &CompilerMessage) -> usize {
+    let occurrences = self.seen.entry(DedupKey::new(compiler_message)).or_default();
+    let targets = occurrences.targets.max(1);
+    occurrences.targets_when_shown = Some(targets);
+    targets
+  }
+
+  /// The shown messages that were reported for more targets after they were shown. Errors are shown
+  /// as soon as they arrive, so their duplicates from other targets usually arrive afterwards.
+  pub fn repeated(self) -> Vec<Repeated> {
+    let mut repeated: Vec<Repeated> =
+      self
+        .seen
+        .into_iter()
+        .filter_map(|(key, occurrences)| {
+          occurrences
+            .targets_when_shown
+            .filter(|targets_when_shown| occurrences.targets > *targets_when_shown)
+            .map(|_| Repeated { key, targets: occurrences.targets })
+        })
+        .collect();
+
+    // Keep the output stable across runs
+    repeated.sort_by(|a, b| a.key.cmp(&b.key));
+    repeated
+  }
+}
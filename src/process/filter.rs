@@ -1,26 +1,138 @@
+use std::path::PathBuf;
+
 use crate::CompilerMessage;
+use crate::compiler_message::CompilerMessageSpan;
+use crate::file_pattern::{normalise_file_name, FilePattern, LineRange};
+use crate::workspace::find_workspace_root;
+
+
+/// The files to show messages for, and the files to hide messages for.
+pub struct FileFilter<'a> {
+  includes: &'a [FilePattern],
+  excludes: &'a [FilePattern],
+  /// Only consider the spans rustc points to as the cause of the message
+  primary_span_only: bool,
+  lines: Option<LineRange>,
+  workspace_root: Option<PathBuf>,
+}
+
+impl<'a> FileFilter<'a> {
+
+  pub fn new(includes: &'a [FilePattern], excludes: &'a [FilePattern], primary_span_only: bool, lines: Option<LineRange>) -> Self {
+    let workspace_root =
+      if includes.is_empty() && excludes.is_empty() {
+        None
+      } else {
+        find_workspace_root()
+      };
+
+    Self {
+      includes,
+      excludes,
+      primary_span_only,
+      lines,
+      workspace_root,
+    }
+  }
+}
+
+
+/// Keeps messages with a span in a file matching any of the includes (if any) within the lines
+/// (if any). Messages are hidden if all their spans are in files matching any of the excludes.
+pub fn by_filename(file_filter: &FileFilter, compiler_message: CompilerMessage) -> Option<CompilerMessage> {
+  let spans: Vec<(String, &CompilerMessageSpan)> =
+    compiler_message
+      .message
+      .spans
+      .iter()
+      .filter(|span| !file_filter.primary_span_only || span.is_primary)
+      .map(|span| (normalise_file_name(&span.file_name, file_filter.workspace_root.as_deref()), span))
+      .collect();
+
+  let matches_any = |file_name: &String, patterns: &[FilePattern]| {
+    patterns
+      .iter()
+      .any(|pattern| pattern.matches(file_name))
+  };
+
+  let within_lines = |span: &CompilerMessageSpan| {
+    file_filter
+      .lines
+      .as_ref()
+      .is_none_or(|lines| lines.overlaps(span.line_start, span.line_end))
+  };
+
+  let included =
+    (file_filter.includes.is_empty() && file_filter.lines.is_none()) ||
+    spans
+      .iter()
+      .any(|(file_name, span)| {
+        (file_filter.includes.is_empty() || matches_any(file_name, file_filter.includes)) && within_lines(span)
+      });
+
+  let excluded =
+    !file_filter.excludes.is_empty() &&
+    !spans.is_empty() &&
+    spans
+      .iter()
+      .all(|(file_name, _)| matches_any(file_name, file_filter.excludes));
+
+  (included && !excluded).then_some(compiler_message)
+}
+
+
+/// Keeps messages for targets named in targets (if any) with a kind in target_kinds (if any).
+/// Hyphens and underscores in target names are treated the same, as Cargo uses both.
+pub fn by_target(targets: &[String], target_kinds: &[String], compiler_message: CompilerMessage) -> Option<CompilerMessage> {
+  let target = &compiler_message.target;
+  let target_name = normalise_target_name(&target.name);
+
+  let name_matches =
+    targets.is_empty() ||
+    targets
+      .iter()
+      .any(|name| normalise_target_name(name) == target_name);
+
+  let kind_matches =
+    target_kinds.is_empty() ||
+    target
+      .kind
+      .iter()
+      .any(|kind| target_kinds.contains(kind));
+
+  (name_matches && kind_matches).then_some(compiler_message)
+}
+
+
+fn normalise_target_name(name: &str) -> String {
+  name.replace('-', "_")
+}
+
+
+/// Keeps messages whose code matches one of include_codes (if any) and none of exclude_codes.
+/// Codes ending in `*` match any code starting with what comes before it. Example: unused_*
+pub fn by_code(include_codes: &[String], exclude_codes: &[String], compiler_message: CompilerMessage) -> Option<CompilerMessage> {
+  let code =
+    compiler_message
+      .message
+      .code
+      .as_ref()
+      .map(|c| c.code.as_str());
+
+  let matches_any = |codes: &[String]| {
+    code.is_some_and(|code| codes.iter().any(|filter| code_matches(filter, code)))
+  };
+
+  let included = include_codes.is_empty() || matches_any(include_codes);
+  let excluded = matches_any(exclude_codes);
+
+  (included && !excluded).then_some(compiler_message)
+}
+
 
-pub fn by_filename(file_to_show_errors_for: Option<String>, matched: Vec<CompilerMessage>) -> Vec<CompilerMessage> {
-  match file_to_show_errors_for {
-    Some(file_name_filter) => {
-      matched
-        .into_iter()
-        .filter(|compiler_message|{
-          let filter_matches =
-            compiler_message
-              .message
-              .spans
-              .iter()
-              .filter(|span|{
-                span
-                  .file_name
-                  .ends_with(&file_name_filter)
-                });
-
-          !filter_matches.collect::<Vec<_>>().is_empty()
-        })
-        .collect::<Vec<_>>()
-    },
-    None => matched
+fn code_matches(filter: &str, code: &str) -> bool {
+  match filter.strip_suffix('*') {
+    Some(prefix) => code.starts_with(prefix),
+    None => code == filter,
   }
 }
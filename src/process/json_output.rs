@@ -0,0 +1,114 @@
+use serde::Serialize;
+
+use crate::CompilerMessage;
+use crate::decoding_error::DecodingError;
+use super::dedup::Repeated;
+use super::output::Output;
+use super::summary::Summary;
+use super::test_results::TestResult;
+
+
+/// A compiler message along with the number of targets it was reported for when it was shown.
+#[derive(Serialize)]
+struct ShownMessage {
+  #[serde(flatten)]
+  compiler_message: CompilerMessage,
+  targets: usize,
+}
+
+
+/// A single line of NDJSON output.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Event {
+  CompilerMessage(Box<ShownMessage>),
+  TestResult(TestResult),
+  DecodingError(DecodingError),
+  Repeated(Repeated),
+  Summary(Summary),
+}
+
+
+/// Writes everything as a single JSON document once the input has been processed.
+#[derive(Serialize, Default)]
+pub struct JsonOutput {
+  compiler_messages: Vec<ShownMessage>,
+  test_results: Vec<TestResult>,
+  decoding_errors: Vec<DecodingError>,
+  repeated: Vec<Repeated>,
+  summary: Summary,
+}
+
+impl JsonOutput {
+  pub fn new() -> Self {
+    Default::default()
+  }
+}
+
+impl Output for JsonOutput {
+
+  fn compiler_message(&mut self, compiler_message: CompilerMessage, targets: usize) {
+    self.compiler_messages.push(ShownMessage { compiler_message, targets })
+  }
+
+  // Only the structured test results are included
+  fn stdout_line(&mut self, _line: String) {}
+
+  fn test_result(&mut self, test_result: TestResult) {
+    self.test_results.push(test_result)
+  }
+
+  fn decoding_error(&mut self, error: DecodingError) {
+    self.decoding_errors.push(error)
+  }
+
+  fn repeated(&mut self, repeated: Repeated) {
+    self.repeated.push(repeated)
+  }
+
+  fn summary(&mut self, summary: Summary) {
+    self.summary = summary;
+    println!("{}", serde_json::to_string(&self).expect("Could not encode output as JSON"))
+  }
+}
+
+
+/// Writes each item as a JSON object on its own line as soon as it arrives.
+pub struct NdJsonOutput;
+
+impl NdJsonOutput {
+
+  pub fn new() -> Self {
+    Self
+  }
+
+  fn write(&self, event: Event) {
+    println!("{}", serde_json::to_string(&event).expect("Could not encode output as JSON"))
+  }
+}
+
+impl Output for NdJsonOutput {
+
+  fn compiler_message(&mut self, compiler_message: CompilerMessage, targets: usize) {
+    self.write(Event::CompilerMessage(Box::new(ShownMessage { compiler_message, targets })))
+  }
+
+  // Only the structured test results are included
+  fn stdout_line(&mut self, _line: String) {}
+
+  fn test_result(&mut self, test_result: TestResult) {
+    self.write(Event::TestResult(test_result))
+  }
+
+  fn decoding_error(&mut self, error: DecodingError) {
+    self.write(Event::DecodingError(error))
+  }
+
+  fn repeated(&mut self, repeated: Repeated) {
+    self.write(Event::Repeated(repeated))
+  }
+
+  fn summary(&mut self, summary: Summary) {
+    self.write(Event::Summary(summary))
+  }
+}
@@ -1,92 +1,68 @@
+use serde::Serialize;
 use crate::CompilerMessage;
+use crate::compiler_message::Level;
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum LevelType {
+  /// Errors, including internal compiler errors
   ErrorLevel(CompilerMessage),
   WarningLevel(CompilerMessage),
+  /// Notes, help and failure notes that are not attached to another message
+  NoteLevel(CompilerMessage),
 }
 
+impl LevelType {
+
+  /// Applies a filter to the message, keeping its level.
+  pub fn filter_message<F>(self, filter: F) -> Option<LevelType>
+    where F: FnOnce(CompilerMessage) -> Option<CompilerMessage>
+  {
+    match self {
+      LevelType::ErrorLevel(cm)   => filter(cm).map(LevelType::ErrorLevel),
+      LevelType::WarningLevel(cm) => filter(cm).map(LevelType::WarningLevel),
+      LevelType::NoteLevel(cm)    => filter(cm).map(LevelType::NoteLevel),
+    }
+  }
+}
+
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
 pub struct LevelStatus {
   pub errors: bool,
   pub warnings: bool
 }
 
 impl LevelStatus {
-  fn copy_errors(self, new_errors: bool) -> Self {
-    Self {
-      errors: new_errors,
-      warnings: self.warnings
-    }
+  pub fn new() -> Self {
+    Default::default()
   }
 
-  fn copy_warnings(self, new_warnings: bool) -> Self {
-    Self {
-      errors: self.errors,
-      warnings: new_warnings
+  /// Record the level of a message as it streams in.
+  pub fn update(&mut self, level_type: &LevelType) {
+    match level_type {
+      LevelType::ErrorLevel(_)   => self.errors = true,
+      LevelType::WarningLevel(_) => self.warnings = true,
+      LevelType::NoteLevel(_)    => (),
     }
   }
 }
 
-pub struct LevelInfo {
-  pub status: LevelStatus,
-  pub level_types: Vec<LevelType>
-}
 
-
-pub fn by_level(filtered_match: Vec<CompilerMessage>) -> LevelInfo {
-  let filtered_by_level: Vec<LevelType> = filter_by_level(filtered_match);
-  let level_status = get_level_status(&filtered_by_level);
-  LevelInfo {
-    status: level_status,
-    level_types: filtered_by_level
+/// Rustc finishes with a warning such as "3 warnings emitted", which is not a warning itself and is
+/// removed. Levels added to rustc after this was written are treated as notes, so they aren't lost.
+pub fn by_level(compiler_message: CompilerMessage) -> Option<LevelType> {
+  match compiler_message.message.level {
+    Level::Error | Level::InternalCompilerError           => Some(LevelType::ErrorLevel(compiler_message)),
+    Level::Warning if is_warning_count(&compiler_message) => None,
+    Level::Warning                                        => Some(LevelType::WarningLevel(compiler_message)),
+    Level::Note | Level::Help | Level::FailureNote        => Some(LevelType::NoteLevel(compiler_message)),
+    Level::Other(_)                                       => Some(LevelType::NoteLevel(compiler_message)),
   }
 }
 
 
-fn filter_by_level(filtered_match: Vec<CompilerMessage>) -> Vec<LevelType> {
-  filtered_match
-    .into_iter()
-    .filter_map(|i| {
-      let level = &i.message.level;
-      match level.as_str() {
-        "error"   => Some(LevelType::ErrorLevel(i)),
-        "warning" => Some(LevelType::WarningLevel(i)),
-        _         => None
-      }
-    })
-    .collect()
+fn is_warning_count(compiler_message: &CompilerMessage) -> bool {
+  let message = &compiler_message.message.message;
+  message.contains("warning emitted") || message.contains("warnings emitted")
 }
-
-
-fn get_level_status(filtered_by_level: &[LevelType]) -> LevelStatus {
-  let init =
-    LevelStatus {
-      errors: false,
-      warnings: false,
-    };
-
-  let result =
-    filtered_by_level
-      .iter()
-      .fold(init, |acc, v| {
-        match v {
-          LevelType::ErrorLevel(_) => {
-            if !acc.errors {
-              acc.copy_errors(true)
-            } else {
-              acc
-            }
-          },
-          LevelType::WarningLevel(_) => {
-            if !acc.warnings {
-              acc.copy_warnings(true)
-            } else {
-              acc
-            }
-          },
-        }
-      });
-
-  result
-}
-
@@ -1,52 +1,178 @@
-use itertools::Itertools;
-
-use crate::Rendered;
 use crate::CompilerMessage;
+use crate::cli::SortOrder;
 use super::level_status::LevelType;
+use super::sort::sort_messages;
+use super::summary::{Counts, LevelCounts};
+use super::test_results::{TestCase, TestResult, TestStatus};
 
-pub fn by_number(mut filtered_by_level: Vec<LevelType>, items_to_show: usize, show_warnings: bool) -> Vec<CompilerMessage> {
-  if !show_warnings {
-    // Errors only
-    filtered_by_level
-      .into_iter()
-      .filter_map(|lt| {
-        match lt {
-          LevelType::ErrorLevel(cm) => Some(cm),
-          _                         => None,
+
+/// The maximum number of messages to show. Limits that are not set are unlimited.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+  /// The number of errors and warnings combined
+  pub items: Option<usize>,
+  pub max_errors: Option<usize>,
+  pub max_warnings: Option<usize>,
+  pub show_warnings: bool,
+  /// Notes are not limited, they are either all shown or all hidden
+  pub show_notes: bool,
+}
+
+
+/// Limits the number of messages shown while they stream in.
+///
+/// Errors are released as soon as they arrive, unless they are sorted. Sorted errors are held until
+/// the input is exhausted, so the limits apply to the sorted errors. Warnings are always shown after
+/// errors, so they are held until the input is exhausted and then fill any remaining slots. Notes
+/// come last.
+pub struct ItemLimit {
+  limits: Limits,
+  sort: SortOrder,
+  shown_errors: usize,
+  held_errors: Vec<CompilerMessage>,
+  held_warnings: Vec<CompilerMessage>,
+  held_notes: Vec<CompilerMessage>,
+}
+
+impl ItemLimit {
+
+  pub fn new(limits: Limits, sort: SortOrder) -> Self {
+    Self {
+      limits,
+      sort,
+      shown_errors: 0,
+      held_errors: vec![],
+      held_warnings: vec![],
+      held_notes: vec![],
+    }
+  }
+
+  /// Returns the message if it should be shown right away. Messages that are removed are recorded
+  /// in the counts for their level.
+  pub fn by_number(&mut self, level_type: LevelType, level_counts: &mut LevelCounts) -> Option<CompilerMessage> {
+    match level_type {
+      LevelType::ErrorLevel(cm) => {
+        if self.sort != SortOrder::Arrival {
+          self.held_errors.push(cm);
+          None
+        } else if self.has_room_for_error() {
+          self.shown_errors += 1;
+          Some(cm)
+        } else {
+          level_counts.over_limit += 1;
+          None
         }
-      })
-      .take(items_to_show)
+      },
+      LevelType::WarningLevel(cm) => {
+        self.held_warnings.push(cm);
+        None
+      },
+      LevelType::NoteLevel(cm) => {
+        self.held_notes.push(cm);
+        None
+      },
+    }
+  }
+
+  /// Returns the held errors, the held warnings that fit in the slots left over and the held notes,
+  /// in the order they should be shown. The messages held are recorded in the counts as shown or over the limit.
+  pub fn finish(mut self, counts: &mut Counts) -> Vec<CompilerMessage> {
+    sort_messages(&mut self.held_errors, self.sort);
+    sort_messages(&mut self.held_warnings, self.sort);
+
+    let errors_to_show =
+      self
+        .limits
+        .items
+        .unwrap_or(usize::MAX)
+        .min(self.limits.max_errors.unwrap_or(usize::MAX))
+        .saturating_sub(self.shown_errors);
+
+    let errors = take_counted(self.held_errors, errors_to_show, &mut counts.errors);
+    let shown_errors = self.shown_errors + errors.len();
+
+    let warnings_to_show =
+      if self.limits.show_warnings {
+        let remaining_items =
+          self
+            .limits
+            .items
+            .map_or(usize::MAX, |items| items.saturating_sub(shown_errors));
+
+        self
+          .limits
+          .max_warnings
+          .unwrap_or(usize::MAX)
+          .min(remaining_items)
+      } else {
+        0
+      };
+
+    let warnings = take_counted(self.held_warnings, warnings_to_show, &mut counts.warnings);
+
+    let notes_to_show = if self.limits.show_notes { usize::MAX } else { 0 };
+    let notes = take_counted(self.held_notes, notes_to_show, &mut counts.notes);
+
+    errors
+      .into_iter()
+      .chain(warnings)
+      .chain(notes)
       .collect()
-  } else {
-      // Both errors and warnings
-      // Sort with errors first, then warnings
-      filtered_by_level
-      .sort_by_key(|lt|{
-        match lt {
-          LevelType::ErrorLevel(_)   => 0,
-          LevelType::WarningLevel(_) => 1
-        }
-      });
+  }
+
+  fn has_room_for_error(&self) -> bool {
+    let within_items = self.limits.items.is_none_or(|items| self.shown_errors < items);
+    let within_max_errors = self.limits.max_errors.is_none_or(|max_errors| self.shown_errors < max_errors);
+
+    within_items && within_max_errors
+  }
+}
+
+
+/// Limits the number of failing tests shown across all the test binaries run.
+pub struct FailureLimit {
+  max_failures: usize,
+  shown: usize,
+}
 
-      // The warnings returned by the Cargo JSON have duplicate elements.
-      // We convert them to Rendered to allow us to remove duplicated with the same rendered output.
-      filtered_by_level
+impl FailureLimit {
+
+  pub fn new(max_failures: usize) -> Self {
+    Self { max_failures, shown: 0 }
+  }
+
+  /// Returns the failed tests of the binary that should be shown, along with the number that are not.
+  pub fn by_number<'a>(&mut self, test_result: &'a TestResult) -> (Vec<&'a TestCase>, usize) {
+    let failed: Vec<&TestCase> =
+      test_result
+        .tests
+        .iter()
+        .filter(|test| test.status == TestStatus::Failed)
+        .collect();
+
+    let total = failed.len();
+    let shown: Vec<&TestCase> =
+      failed
         .into_iter()
-        .filter_map(|lt|{
-          match lt {
-            LevelType::ErrorLevel(cm)   => Some(Rendered::new(cm)),
-            LevelType::WarningLevel(cm) => {
-              if cm.message.message.contains("warning emitted") { // Also remove messages that say "warning emitted"
-                None
-              } else {
-                Some(Rendered::new(cm))
-              }
-            },
-          }
-        })
-        .unique() // Removes duplicates
-        .map(|r| r.items)
-        .take(items_to_show)
-        .collect()
+        .take(self.max_failures.saturating_sub(self.shown))
+        .collect();
+
+    self.shown += shown.len();
+    let not_shown = total - shown.len();
+    (shown, not_shown)
   }
 }
+
+
+fn take_counted(messages: Vec<CompilerMessage>, number: usize, level_counts: &mut LevelCounts) -> Vec<CompilerMessage> {
+  let total = messages.len();
+  let taken: Vec<CompilerMessage> =
+    messages
+      .into_iter()
+      .take(number)
+      .collect();
+
+  level_counts.shown += taken.len();
+  level_counts.over_limit += total - taken.len();
+  taken
+}
@@ -2,5 +2,13 @@ pub mod compiler_messages;
 pub mod stdout;
 pub mod level_status;
 pub mod limit;
+pub mod dedup;
+pub mod sort;
 pub mod filter;
-pub mod all_messages;
+pub mod pipeline;
+pub mod outcome;
+pub mod output;
+pub mod json_output;
+pub mod test_results;
+pub mod nextest;
+pub mod summary;
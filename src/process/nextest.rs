@@ -0,0 +1,132 @@
+use std::format as s;
+
+use super::test_results::TestStatus;
+
+
+/// A line cargo-nextest prints as each test finishes. Examples:
+///         PASS [   0.004s] failing-tests tests::adds_numbers
+///      SIGSEGV [   0.150s] failing-tests::api api_crashes
+///   TRY 2 FAIL [   0.005s] failing-tests tests::flaky
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusLine {
+  pub status: TestStatus,
+  /// The binary id followed by the test name. Example: failing-tests::api api_crashes
+  pub name: String,
+  pub duration: String,
+}
+
+
+/// The line that ends the run. Example:
+///      Summary [   0.160s] 6 tests run: 3 passed, 3 failed, 1 skipped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SummaryLine {
+  pub duration: String,
+  pub passed: u32,
+  /// Includes the tests that timed out or could not be run
+  pub failed: u32,
+  pub skipped: u32,
+}
+
+
+/// Nextest run ID 6b2f3c1e-58a4-4d4f-9d43-3c8b1f0e2a7d with nextest profile: default
+/// Starting 6 tests across 2 binaries (1 test skipped)
+///
+/// Older versions of nextest only print the second line.
+pub fn is_start_line(line: &str) -> bool {
+  let line = line.trim();
+  line.starts_with("Nextest run ID ") ||
+    (line.starts_with("Starting ") && line.contains(" across ") && line.contains(" binar"))
+}
+
+
+pub fn parse_status_line(line: &str) -> Option<StatusLine> {
+  let (prefix, rest) = line.trim().split_once(" [")?;
+  let (duration, name) = rest.split_once("] ")?;
+
+  // Retries are prefixed with the attempt. Example: TRY 2 FAIL
+  let status_word = prefix.split_whitespace().last()?;
+  let status =
+    match status_word {
+      "PASS" | "LEAK" => TestStatus::Passed,
+      "SKIP"          => TestStatus::Ignored,
+      "FAIL" | "LEAK-FAIL" | "ABORT" | "TIMEOUT" => TestStatus::Failed,
+      // The test was killed by a signal. Example: SIGSEGV
+      signal if signal.starts_with("SIG") => TestStatus::Failed,
+      // Such as START, SLOW and RETRY, which don't say how the test ended
+      _ => return None,
+    };
+
+  Some(StatusLine { status, name: name.trim().to_owned(), duration: duration.trim().to_owned() })
+}
+
+
+/// The header before the output captured for a failed test, which depends on the version of nextest:
+/// --- STDOUT:              failing-tests tests::adds_wrong_numbers ---
+/// ──── STDERR:             failing-tests tests::adds_wrong_numbers
+pub fn parse_captured_header(line: &str) -> Option<String> {
+  let rest = line.trim().trim_start_matches(['-', '─']).trim_start();
+  let name =
+    rest
+      .strip_prefix("STDOUT:")
+      .or_else(|| rest.strip_prefix("STDERR:"))?;
+
+  Some(name.trim().trim_end_matches(['-', '─']).trim_end().to_owned())
+}
+
+
+/// The line nextest prints before the summary. Example: ------------
+pub fn is_separator(line: &str) -> bool {
+  let line = line.trim();
+  !line.is_empty() && line.chars().all(|c| c == '-' || c == '─')
+}
+
+
+/// Lines nextest prints about the progress of the run, which don't say how a test ended. Examples:
+///         SLOW [> 60.000s] failing-tests tests::waits
+///    Cancelling due to test failure: 1 test still running
+pub fn is_progress_line(line: &str) -> bool {
+  let line = line.trim_start();
+  let word = line.split_whitespace().next().unwrap_or_default();
+
+  ["START", "SLOW", "RETRY", "TRY", "TERMINATING"].contains(&word) ||
+    line.starts_with("Cancelling ") ||
+    line.starts_with("Canceling ")
+}
+
+
+/// Parses the counts after the number of tests run, which can include details in brackets:
+///      Summary [   0.160s] 6 tests run: 3 passed (1 slow), 3 failed, 1 skipped
+pub fn parse_summary_line(line: &str) -> Option<SummaryLine> {
+  let rest = line.trim().strip_prefix("Summary [")?;
+  let (duration, counts) = rest.split_once(']')?;
+  let (_, counts) = counts.split_once(':')?;
+
+  let mut summary = SummaryLine { duration: duration.trim().to_owned(), ..Default::default() };
+
+  counts
+    .split(',')
+    .filter_map(|part| part.trim().split_once(' '))
+    .for_each(|(count, label)| {
+      let count: u32 = count.parse().unwrap_or(0);
+      let label = label.split(" (").next().unwrap_or(label).trim();
+      match label {
+        "passed"                               => summary.passed += count,
+        "failed" | "timed out" | "exec failed" => summary.failed += count,
+        "skipped"                              => summary.skipped += count,
+        _                                      => (),
+      }
+    });
+
+  Some(summary)
+}
+
+
+/// The lines the test harness prints around a test's own output, when nextest runs a single test.
+pub fn is_harness_line(line: &str, test_name: &str) -> bool {
+  let trimmed = line.trim();
+  (trimmed.starts_with("running ") && (trimmed.ends_with(" test") || trimmed.ends_with(" tests"))) ||
+    trimmed.starts_with("test result: ") ||
+    trimmed == "failures:" ||
+    trimmed == test_name ||
+    trimmed.starts_with(&s!("test {} ... ", test_name))
+}
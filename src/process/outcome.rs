@@ -0,0 +1,45 @@
+use std::process::ExitCode;
+use super::level_status::LevelStatus;
+
+/// Used when quiet itself fails, such as when its options are invalid or Cargo can't be run.
+pub const QUIET_ERROR_EXIT_CODE: u8 = 4;
+
+/// The overall result of a run, used as quiet's exit code so it can be used in scripts, hooks and CI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+  Success,
+  CompilationErrors,
+  TestFailures,
+  DecodingErrors,
+}
+
+impl Outcome {
+
+  /// Compilation errors take precedence over test failures, which take precedence over lines quiet
+  /// could not decode. Warnings are only counted as compilation errors when they are fatal. The level
+  /// status is of the messages decoded, including those the filters removed.
+  pub fn new(level_status: LevelStatus, test_failures: bool, decoding_errors: usize, fatal_warnings: bool) -> Self {
+    if level_status.errors || (fatal_warnings && level_status.warnings) {
+      Outcome::CompilationErrors
+    } else if test_failures {
+      Outcome::TestFailures
+    } else if decoding_errors > 0 {
+      Outcome::DecodingErrors
+    } else {
+      Outcome::Success
+    }
+  }
+
+  pub fn is_success(&self) -> bool {
+    *self == Outcome::Success
+  }
+
+  pub fn exit_code(&self) -> ExitCode {
+    match self {
+      Outcome::Success           => ExitCode::SUCCESS,
+      Outcome::CompilationErrors => ExitCode::from(1),
+      Outcome::TestFailures      => ExitCode::from(2),
+      Outcome::DecodingErrors    => ExitCode::from(3),
+    }
+  }
+}
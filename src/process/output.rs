@@ -0,0 +1,36 @@
+use crate::CompilerMessage;
+use crate::cli::{Cli, OutputFormat};
+use crate::decoding_error::DecodingError;
+use super::dedup::Repeated;
+use super::json_output::{JsonOutput, NdJsonOutput};
+use super::stdout::{TextOutput, TextView};
+use super::summary::Summary;
+use super::test_results::TestResult;
+
+/// Where the pipeline sends everything that made it through the filters and limits.
+pub trait Output {
+  /// The number of targets the message has been reported for is known at the time it is shown.
+  fn compiler_message(&mut self, compiler_message: CompilerMessage, targets: usize);
+
+  fn stdout_line(&mut self, line: String);
+
+  fn test_result(&mut self, test_result: TestResult);
+
+  fn decoding_error(&mut self, error: DecodingError);
+
+  /// Called once all the input has been processed, for each message shown that was reported for
+  /// more targets after it was shown.
+  fn repeated(&mut self, repeated: Repeated);
+
+  /// Called once all the input has been processed.
+  fn summary(&mut self, summary: Summary);
+}
+
+
+pub fn output_for(cli: &Cli) -> Box<dyn Output> {
+  match cli.output {
+    OutputFormat::Text   => Box::new(TextOutput::new(cli.filtered_out.clone(), TextView::from_cli(cli), cli.max_failures)),
+    OutputFormat::Json   => Box::new(JsonOutput::new()),
+    OutputFormat::Ndjson => Box::new(NdJsonOutput::new()),
+  }
+}
@@ -0,0 +1,161 @@
+use crate::CompilerMessage;
+use crate::cli::Cli;
+use crate::compiler_message::Level;
+use crate::fix::apply_fixes;
+use crate::workspace::workspace_members;
+use super::compiler_messages::ItemTypes;
+use super::dedup::Dedup;
+use super::filter::{by_code, by_filename, by_target, FileFilter};
+use super::level_status::{by_level, LevelStatus, LevelType};
+use super::limit::{ItemLimit, Limits};
+use super::outcome::Outcome;
+use super::output::output_for;
+use super::summary::{BuildSummary, Counts, FileTable, Summary};
+use super::test_results::TestResults;
+
+
+/// Processes each item as it arrives. Compiler messages that make it through the filters, the
+/// deduplication and the item limit are output immediately (unless they are sorted), while the
+/// summary is output once the input is exhausted.
+pub fn run_pipeline<I>(cli: &Cli, items: I) -> Outcome
+  where I: Iterator<Item = ItemTypes>
+{
+  let file_filter = FileFilter::new(&cli.file_filter, &cli.exclude_file, cli.primary_span_only, cli.lines);
+  let mut dedup = Dedup::new();
+  let mut item_limit = ItemLimit::new(limits_for(cli), cli.sort);
+  let mut output = output_for(cli);
+  let mut test_results = TestResults::new();
+  let mut level_status = LevelStatus::new();
+  // The exit code is based on every message decoded, so a build can't pass because its errors were filtered out
+  let mut decoded_status = LevelStatus::new();
+  let mut counts = Counts::new();
+  let mut file_table = FileTable::new();
+  let mut internal_compiler_errors = vec![];
+  let mut build = BuildSummary::new();
+  let mut decoding_errors = 0;
+  // Only the messages that are shown are fixed
+  let mut shown = vec![];
+
+  for item in items {
+    match item {
+      ItemTypes::CompilerMessageType(cm) => {
+        // The compiler crashing is always worth knowing about, even if the message is filtered out
+        if cm.message.level == Level::InternalCompilerError {
+          internal_compiler_errors.push(cm.message.message.clone());
+          level_status.errors = true
+        }
+
+        let Some(level_type) = by_level(*cm) else { continue };
+        decoded_status.update(&level_type);
+        let level_counts = counts.for_level(&level_type);
+        level_counts.decoded += 1;
+
+        let maybe_level_type =
+          filter_counted(level_type, &mut level_counts.removed_by_target_filter, |cm| by_target(&cli.target, &cli.target_kind, cm))
+            .and_then(|level_type| filter_counted(level_type, &mut level_counts.removed_by_file_filter, |cm| by_filename(&file_filter, cm)))
+            .and_then(|level_type| filter_counted(level_type, &mut level_counts.removed_by_code_filter, |cm| by_code(&cli.include_code, &cli.exclude_code, cm)))
+            .and_then(|level_type| filter_counted(level_type, &mut level_counts.duplicates, |cm| dedup.by_duplicate(cm)));
+
+        if let Some(level_type) = maybe_level_type {
+          level_status.update(&level_type);
+          file_table.update(&level_type);
+          if let Some(compiler_message) = item_limit.by_number(level_type, level_counts) {
+            level_counts.shown += 1;
+            if cli.fix {
+              shown.push(compiler_message.clone())
+            }
+            let targets = dedup.shown(&compiler_message);
+            if !cli.summary_only {
+              output.compiler_message(compiler_message, targets)
+            }
+          }
+        }
+      },
+      ItemTypes::CargoEventType(cargo_event) => build.update(&cargo_event),
+      ItemTypes::TestEventType(test_event) => {
+        if let Some(test_result) = test_results.process_event(test_event) {
+          output.test_result(test_result)
+        }
+      },
+      ItemTypes::StdoutLineType(line) => {
+        if let Some(test_result) = test_results.process_line(&line) {
+          output.test_result(test_result)
+        }
+        test_results
+          .shown_lines(line)
+          .into_iter()
+          .for_each(|line| output.stdout_line(line))
+      },
+      ItemTypes::ErrorType(error) => {
+        decoding_errors += 1;
+        output.decoding_error(error)
+      },
+    }
+  }
+
+  if let Some(line) = test_results.finish_lines() {
+    output.stdout_line(line)
+  }
+
+  item_limit
+    .finish(&mut counts)
+    .into_iter()
+    .for_each(|compiler_message| {
+      if cli.fix {
+        shown.push(compiler_message.clone())
+      }
+      let targets = dedup.shown(&compiler_message);
+      if !cli.summary_only {
+        output.compiler_message(compiler_message, targets)
+      }
+    });
+
+  if !cli.summary_only {
+    dedup
+      .repeated()
+      .into_iter()
+      .for_each(|repeated| output.repeated(repeated));
+  }
+
+  build.add_not_attempted(&workspace_members());
+
+  if build.failed_without_errors(&counts) {
+    level_status.errors = true
+  }
+
+  if build.failed() {
+    decoded_status.errors = true
+  }
+
+  output.summary(Summary { level_status, counts, internal_compiler_errors, build, files: file_table.files() });
+
+  if cli.fix {
+    apply_fixes(&shown, cli.dry_run)
+  }
+
+  Outcome::new(decoded_status, test_results.has_failures(), decoding_errors, cli.fatal_warnings)
+}
+
+
+/// Applies a filter to the message, counting it as removed when the filter returns nothing.
+fn filter_counted<F>(level_type: LevelType, removed: &mut usize, filter: F) -> Option<LevelType>
+  where F: FnOnce(CompilerMessage) -> Option<CompilerMessage>
+{
+  let maybe_level_type = level_type.filter_message(filter);
+  if maybe_level_type.is_none() {
+    *removed += 1
+  }
+  maybe_level_type
+}
+
+
+fn limits_for(cli: &Cli) -> Limits {
+  Limits {
+    items: cli.items,
+    max_errors: cli.max_errors,
+    max_warnings: cli.max_warnings,
+    // Asking for a number of warnings implies wanting to see them
+    show_warnings: cli.show_warnings || cli.max_warnings.is_some(),
+    show_notes: cli.show_notes,
+  }
+}
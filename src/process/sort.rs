@@ -0,0 +1,60 @@
+use std::cmp::Ordering;
+
+use crate::CompilerMessage;
+use crate::cli::SortOrder;
+use crate::compiler_message::CompilerMessageSpan;
+
+
+/// Sorts messages by the location of their primary span, or their code. Messages without a primary
+/// span or a code come last. The sort is stable, so messages that compare equal stay in arrival order.
+pub fn sort_messages(messages: &mut [CompilerMessage], sort: SortOrder) {
+  match sort {
+    SortOrder::Arrival => (),
+    SortOrder::File    => messages.sort_by(by_file),
+    SortOrder::Line    => messages.sort_by(|a, b| by_file(a, b).then_with(|| by_line(a, b))),
+    SortOrder::Code    => messages.sort_by(|a, b| by_code(a, b).then_with(|| by_file(a, b)).then_with(|| by_line(a, b))),
+  }
+}
+
+
+fn by_file(a: &CompilerMessage, b: &CompilerMessage) -> Ordering {
+  last_if_none(
+    primary_span(a).map(|span| &span.file_name),
+    primary_span(b).map(|span| &span.file_name)
+  )
+}
+
+
+fn by_line(a: &CompilerMessage, b: &CompilerMessage) -> Ordering {
+  last_if_none(
+    primary_span(a).map(|span| (span.line_start, span.column_start)),
+    primary_span(b).map(|span| (span.line_start, span.column_start))
+  )
+}
+
+
+fn by_code(a: &CompilerMessage, b: &CompilerMessage) -> Ordering {
+  last_if_none(
+    a.message.code.as_ref().map(|code| &code.code),
+    b.message.code.as_ref().map(|code| &code.code)
+  )
+}
+
+
+fn primary_span(compiler_message: &CompilerMessage) -> Option<&CompilerMessageSpan> {
+  compiler_message
+    .message
+    .spans
+    .iter()
+    .find(|span| span.is_primary)
+}
+
+
+fn last_if_none<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+  match (a, b) {
+    (Some(a), Some(b)) => a.cmp(&b),
+    (Some(_), None)    => Ordering::Less,
+    (None, Some(_))    => Ordering::Greater,
+    (None, None)       => Ordering::Equal,
+  }
+}
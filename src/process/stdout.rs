@@ -2,7 +2,13 @@ use std::collections::HashMap;
 use std::format as s;
 use ansi_term::Color::{Red, Green, Yellow, Blue, RGB};
 use crate::CompilerMessage;
-use super::level_status::LevelStatus;
+use crate::cli::Cli;
+use crate::decoding_error::DecodingError;
+use super::dedup::Repeated;
+use super::output::Output;
+use super::summary::{primary_file_name, CrateBuildStatus, CrateStatus, FileCounts, LevelCounts, Summary};
+use super::limit::FailureLimit;
+use super::test_results::{TestCase, TestResult, TestStatus};
 use std::time::SystemTime;
 
 
@@ -25,16 +31,64 @@ pub fn print_start_banner() {
 }
 
 
-pub fn print_compiler_output(constrained_matches: Vec<CompilerMessage>, level_status: LevelStatus) {
-  println!();
-  constrained_matches
-    .into_iter()
-    .for_each(|compiler_message|{
-      println!("*** {} >>> {}", compiler_message.target.src_path, compiler_message.message.rendered)
-    });
+/// Print a single compiler message as soon as it has been let through. Messages reported for more
+/// than one target are shown once, with the number of targets.
+pub fn print_compiler_message(compiler_message: CompilerMessage, targets: usize) {
+  println!("*** {}{} >>> {}", compiler_message.target.src_path, targets_message(targets), compiler_message.message.rendered)
+}
+
+
+/// Let the user know a message already shown was also reported for other targets.
+/// Example: *** error[E0308]: mismatched types at src/lib.rs:3:20 (x3 targets) ***
+pub fn print_repeated(repeated: Repeated) {
+  let key = repeated.key;
+  let code = key.code.map(|code| s!("[{}]", code)).unwrap_or_default();
+  let location =
+    match (key.file_name, key.line, key.column) {
+      (Some(file_name), Some(line), Some(column)) => s!(" at {}:{}:{}", file_name, line, column),
+      _                                           => "".to_owned(),
+    };
+
+  println!("{}", RGB(128, 128, 128).paint(s!("*** {}{}: {}{}{} ***", key.level.as_str(), code, key.message, location, targets_message(repeated.targets))))
+}
+
+
+fn targets_message(targets: usize) -> String {
+  if targets > 1 {
+    s!(" (x{} targets)", targets)
+  } else {
+    "".to_owned()
+  }
+}
+
+
+/// Clear the terminal and move the cursor to the top left, so each run in watch mode starts on a
+/// clean screen.
+pub fn clear_screen() {
+  print!("\x1B[2J\x1B[1;1H");
+}
+
 
+pub fn print_summary(summary: Summary) {
+  summary
+    .internal_compiler_errors
+    .iter()
+    .for_each(|message| println!("\n{}", Red.bold().paint(s!("!!! The compiler crashed with an internal compiler error: {} !!!", message))));
+
+  summary
+    .build
+    .build_script_failures
+    .iter()
+    .for_each(|package| println!("\n{}", Red.paint(s!("!!! The build script of {} failed !!!", package))));
+
+  if summary.build.success == Some(false) && summary.counts.errors.decoded == 0 && summary.build.build_script_failures.is_empty() {
+    println!("\n{}", Red.paint("!!! Cargo reported that the build failed, without any compilation errors !!!"))
+  }
+
+  let level_status = summary.level_status;
   let output_type =
     match (level_status.errors, level_status.warnings) {
+      (false, _) if summary.counts.errors.decoded > 0 => OutputType::Error("!!! There are compilation errors, but none of them match the filters !!!"),
       (true, true)  => OutputType::Error("!!! There are compilation errors and warnings !!!"),
       (true, false) => OutputType::Error("!!! There are compilation errors !!!"),
       (false, true) => OutputType::Warning("*** No compilation errors (but there are warnings) ***"),
@@ -46,108 +100,466 @@ pub fn print_compiler_output(constrained_matches: Vec<CompilerMessage>, level_st
     OutputType::Warning(m) => println!("\n{}", Yellow.paint(m)),
     OutputType::Success(m) => println!("\n{}", Green.paint(m)),
   }
+
+  // Notes are hidden by default, so they are only mentioned when they are shown
+  let notes = Some(("Notes", summary.counts.notes)).filter(|(_, level_counts)| level_counts.shown > 0);
+
+  [("Errors", summary.counts.errors), ("Warnings", summary.counts.warnings)]
+    .into_iter()
+    .filter(|(_, level_counts)| level_counts.decoded > 0)
+    .chain(notes)
+    .for_each(|(level, level_counts)| println!("{}", RGB(128, 128, 128).paint(counts_message(level, level_counts))));
+
+  if !summary.build.built.is_empty() {
+    println!("{}", RGB(128, 128, 128).paint(s!("*** Built: {} ***", summary.build.built.join(", "))))
+  }
+
+  // The status of each crate is only worth knowing when the build stopped early
+  if summary.build.has_failed_crates() {
+    print_crates(&summary.build.crates)
+  }
 }
 
-pub fn print_errors(errors: Vec<String>) {
-    errors
-      .into_iter()
-      .for_each(|e| {
-        println!("{}", e)
-      })
+
+/// Example:
+///   *** Crates ***
+///   alpha: compiled
+///   beta: failed (lib)
+///   gamma: not attempted
+fn print_crates(crates: &[CrateStatus]) {
+  println!("{}", RGB(128, 128, 128).paint("*** Crates ***"));
+  crates
+    .iter()
+    .for_each(|crate_status| {
+      let target = crate_status.failed_target.as_ref().map(|target| s!(" ({})", target)).unwrap_or_default();
+      let line = s!("{}: {}{}", crate_status.name, crate_status.status.as_str(), target);
+      match crate_status.status {
+        CrateBuildStatus::Compiled     => println!("{}", Green.paint(line)),
+        CrateBuildStatus::Failed       => println!("{}", Red.paint(line)),
+        CrateBuildStatus::NotAttempted => println!("{}", RGB(128, 128, 128).paint(line)),
+      }
+    });
 }
 
 
-pub fn print_stdout_lines(stdout_lines: Vec<String>, filtered_out: Vec<String>) {
-  let line_types: Vec<LineType> = get_line_types(stdout_lines);
-  let stdout_lines: Vec<String> = get_stdout_lines(line_types, filtered_out);
+/// Lets the user know how much work remains, and why the messages not shown were removed.
+/// Example: *** Errors: 6 decoded, 1 removed by file filters, 4 over the limit, 1 shown ***
+fn counts_message(level: &str, level_counts: LevelCounts) -> String {
+  let removed =
+    [
+      (level_counts.removed_by_target_filter, "removed by target filters"),
+      (level_counts.removed_by_file_filter, "removed by file filters"),
+      (level_counts.removed_by_code_filter, "removed by code filters"),
+      (level_counts.duplicates, "duplicates"),
+      (level_counts.over_limit, "over the limit"),
+    ];
+
+  let details: Vec<String> =
+    std::iter::once(s!("{} decoded", level_counts.decoded))
+      .chain(
+        removed
+          .into_iter()
+          .filter(|(count, _)| *count > 0)
+          .map(|(count, reason)| s!("{} {}", count, reason))
+      )
+      .chain(std::iter::once(s!("{} shown", level_counts.shown)))
+      .collect();
 
-  stdout_lines
-    .into_iter()
-    .for_each(|line| println!("{}", line))
+  s!("*** {}: {} ***", level, details.join(", "))
 }
 
+pub fn print_error(error: DecodingError) {
+  println!("******************* Failed to decode {} from this line: {}\ncause: {}", error.decoding, Red.paint(error.line), error.cause)
+}
 
-fn get_stdout_lines(line_types: Vec<LineType>, filtered_out: Vec<String>) -> Vec<String> {
-  let mut test_results_buffer: HashMap<&str, u32> = HashMap::new();
 
-  line_types
-    .into_iter()
-    .filter_map(|line_type| {
-      match line_type {
-        LineType::Empty => None,
-        LineType::Failures(line) => {
-          let dots = success_dots_string(test_results_buffer.get("success"));
-          Some(failure_line_string(line.as_str(), dots.as_deref()))
-        },
-        LineType::TestResultFailed(line) => {
-          // Clear the test success
-          test_results_buffer.clear();
-          Some(test_failure_string(line.as_str()))
-        },
-        LineType::TestResultOk(line) => {
-          // Print out the collected tests
-          let dots = success_dots_string(test_results_buffer.get("success"));
-          let output = test_success_string(line.as_str(), dots.as_deref());
-          test_results_buffer.clear();
-          Some(output)
-        },
-        LineType::TestDots(line) => Some(test_run_dots_string(line.as_str())),
-        LineType::Finished => None,
-        LineType::Compiling => None,
-        LineType::Error => None,
-        LineType::Warning => None,
-        LineType::Running(line) => Some(test_name_string(line.as_str())),
-        LineType::SingleTestOk => {
-          // TODO: Move to a function
-          let existing_success_count = test_results_buffer.get("success");
-          if let Some(success_count) = existing_success_count {
-            test_results_buffer.insert("success", success_count + 1);
-          } else {
-            test_results_buffer.insert("success", 1);
-          }
-          None
-        },
-        LineType::SingleTestFailed(line) => Some(failed_test_name_string(line.as_str())),
-        LineType::Unprocessed(line) => default_stdout_string(line.as_str(), &filtered_out),
+/// How compiler messages are laid out in text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextView {
+  /// Each message is printed as soon as it is shown
+  Flat,
+  /// Messages are printed under a header for their file once the input is exhausted
+  GroupedByFile,
+  /// Only the number of errors and warnings in each file is printed
+  SummaryOnly,
+}
+
+impl TextView {
+  pub fn from_cli(cli: &Cli) -> Self {
+    if cli.summary_only {
+      TextView::SummaryOnly
+    } else if cli.group_by_file {
+      TextView::GroupedByFile
+    } else {
+      TextView::Flat
+    }
+  }
+}
+
+
+/// The messages shown for a file, along with the number of targets each was reported for.
+struct FileGroup {
+  file_name: Option<String>,
+  compiler_messages: Vec<(CompilerMessage, usize)>,
+}
+
+
+/// Prints everything as coloured text for people to read.
+pub struct TextOutput {
+  stdout_lines: StdoutLines,
+  view: TextView,
+  failure_limit: Option<FailureLimit>,
+  file_groups: Vec<FileGroup>,
+  repeated: Vec<Repeated>,
+}
+
+impl TextOutput {
+  pub fn new(filtered_out: Vec<String>, view: TextView, max_failures: Option<usize>) -> Self {
+    Self {
+      stdout_lines: StdoutLines::new(filtered_out),
+      view,
+      failure_limit: max_failures.map(FailureLimit::new),
+      file_groups: vec![],
+      repeated: vec![],
+    }
+  }
+
+  fn group_by_file(&mut self, compiler_message: CompilerMessage, targets: usize) {
+    let file_name = primary_file_name(&compiler_message);
+    match self.file_groups.iter_mut().find(|group| group.file_name == file_name) {
+      Some(group) => group.compiler_messages.push((compiler_message, targets)),
+      None        => self.file_groups.push(FileGroup { file_name, compiler_messages: vec![(compiler_message, targets)] }),
+    }
+  }
+}
+
+impl Output for TextOutput {
+
+  fn compiler_message(&mut self, compiler_message: CompilerMessage, targets: usize) {
+    match self.view {
+      TextView::Flat          => print_compiler_message(compiler_message, targets),
+      TextView::GroupedByFile => self.group_by_file(compiler_message, targets),
+      TextView::SummaryOnly   => (),
+    }
+  }
+
+  fn stdout_line(&mut self, line: String) {
+    self.stdout_lines.print_stdout_line(line)
+  }
+
+  // Test results read from libtest's text output are printed as they are read, apart from the
+  // failing tests. Other results are printed in the same format
+  fn test_result(&mut self, test_result: TestResult) {
+    let (failed_tests, not_shown) =
+      match self.failure_limit.as_mut() {
+        Some(failure_limit) => failure_limit.by_number(&test_result),
+        None => (test_result.tests.iter().filter(|test| test.status == TestStatus::Failed).collect(), 0),
+      };
+
+    // Passing tests are printed as dots, as they are for libtest's text output
+    if !test_result.printed_as_read && test_result.passed > 0 {
+      success_dots_string(Some(&test_result.passed)).iter().for_each(|dots| println!("{}", dots))
+    }
+
+    failed_tests
+      .into_iter()
+      .for_each(print_failed_test);
+
+    if not_shown > 0 {
+      println!("{}", RGB(128, 128, 128).paint(s!("*** {} more failing {} not shown ***", not_shown, if not_shown == 1 { "test" } else { "tests" })))
+    }
+
+    if !test_result.printed_as_read {
+      let line = test_result_line(&test_result);
+      if test_result.success {
+        println!("{}", test_success_string(&line, None))
+      } else {
+        println!("{}", test_failure_string(&line))
       }
-    })
-    .collect()
+    }
+  }
+
+  fn decoding_error(&mut self, error: DecodingError) {
+    print_error(error)
+  }
+
+  fn repeated(&mut self, repeated: Repeated) {
+    match self.view {
+      TextView::Flat          => print_repeated(repeated),
+      // Printed after the groups, so they come after the messages they refer to
+      TextView::GroupedByFile => self.repeated.push(repeated),
+      TextView::SummaryOnly   => (),
+    }
+  }
+
+  fn summary(&mut self, summary: Summary) {
+    match self.view {
+      TextView::Flat => (),
+      TextView::GroupedByFile => {
+        std::mem::take(&mut self.file_groups)
+          .into_iter()
+          .for_each(|group| print_file_group(group, &summary.files));
+
+        std::mem::take(&mut self.repeated)
+          .into_iter()
+          .for_each(print_repeated);
+      },
+      TextView::SummaryOnly => print_file_table(&summary.files),
+    }
+
+    print_summary(summary)
+  }
 }
 
 
-fn get_line_types(stdout_lines: Vec<String>) -> Vec<LineType> {
-  stdout_lines
+/// Example: ---------- src/github.rs: 3 errors, 1 warning ----------
+fn print_file_group(group: FileGroup, files: &[FileCounts]) {
+  let file_counts = files.iter().find(|file_counts| file_counts.file_name == group.file_name);
+  let counts =
+    file_counts
+      .map(|file_counts| s!(": {}, {}", plural(file_counts.errors, "error"), plural(file_counts.warnings, "warning")))
+      .unwrap_or_default();
+
+  println!("{}", Blue.paint(s!("---------- {}{} ----------", display_file_name(&group.file_name), counts)));
+
+  group
+    .compiler_messages
     .into_iter()
+    .for_each(|(compiler_message, targets)| {
+      if targets > 1 {
+        println!("{}", RGB(128, 128, 128).paint(s!("*** Reported for {} targets ***", targets)))
+      }
+      println!("{}", compiler_message.message.rendered)
+    });
+}
+
+
+/// Prints the number of errors and warnings in each file as a table.
+fn print_file_table(files: &[FileCounts]) {
+  let file_names: Vec<&str> =
+    files
+      .iter()
+      .map(|file_counts| display_file_name(&file_counts.file_name))
+      .collect();
+
+  let width =
+    file_names
+      .iter()
+      .map(|file_name| file_name.len())
+      .chain(std::iter::once("File".len()))
+      .max()
+      .unwrap_or_default();
+
+  println!("{:<width$}  {:>6}  {:>8}", "File", "Errors", "Warnings");
+  files
+    .iter()
+    .zip(file_names)
+    .for_each(|(file_counts, file_name)| println!("{:<width$}  {:>6}  {:>8}", file_name, file_counts.errors, file_counts.warnings));
+}
+
+
+fn display_file_name(file_name: &Option<String>) -> &str {
+  file_name.as_deref().unwrap_or("(no location)")
+}
+
+
+fn plural(number: usize, word: &str) -> String {
+  if number == 1 {
+    s!("1 {}", word)
+  } else {
+    s!("{} {}s", number, word)
+  }
+}
+
+
+/// Writes a result in the same format as libtest's summary line.
+fn test_result_line(test_result: &TestResult) -> String {
+  let duration = test_result.duration.as_ref().map(|duration| s!("; finished in {}", duration)).unwrap_or_default();
+  s!(
+    "test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out{}",
+    if test_result.success { "ok" } else { "FAILED" },
+    test_result.passed,
+    test_result.failed,
+    test_result.ignored,
+    test_result.measured,
+    test_result.filtered_out,
+    duration
+  )
+}
+
+
+/// Prints a failed test as a compact block, with where and why it panicked ahead of what it
+/// printed. Example:
+/// ---- tests::adds_wrong_numbers at src/lib.rs:17:5 ----
+/// assertion `left == right` failed
+///   left: 4
+///  right: 5
+/// stdout: adding 2 and 2
+fn print_failed_test(test: &TestCase) {
+  let location = test.panic.as_ref().and_then(|panic| panic.location.as_ref());
+  match location {
+    Some(location) => {
+      let location = s!("{}:{}:{}", location.file_name, location.line, location.column);
+      println!("{} at {} {}", Red.paint(s!("---- {}", test.name)), Blue.paint(location), Red.paint("----"))
+    },
+    // Such as when the test was killed by a signal
+    None => println!("{}", Red.paint(s!("---- {} ----", test.name))),
+  }
+
+  if let Some(panic) = &test.panic {
+    println!("{}", panic_message_string(&panic.message))
+  }
+
+  test
+    .stdout
+    .iter()
+    .for_each(|line| println!("{} {}", RGB(133, 138, 118).paint("stdout:"), line));
+}
+
+
+/// Highlights the left and right values of a failed assertion, so they are easy to tell apart.
+/// Values printed with {:#?} span several lines, which are highlighted along with their label.
+fn panic_message_string(message: &str) -> String {
+  let mut side = None;
+
+  message
+    .lines()
     .map(|line| {
-      if line.is_empty() {
-        LineType::Empty
-      } else if line == "failures:" {
-        LineType::Failures(line)
-      } else if line.starts_with("test result: FAILED.") {
-        LineType::TestResultFailed(line)
-      } else if line.starts_with("test result: ok.") {
-        LineType::TestResultOk(line)
-      } else if line.split_inclusive('.').count() == line.len()  {
-        LineType::TestDots(line)
-      } else if line.trim().starts_with("Finished ") {
-        LineType::Finished
-      } else if line.trim().starts_with("Compiling ") {
-        LineType::Compiling
-      } else if line.trim().starts_with("error: ") {
-        LineType::Error
-      } else if line.trim().starts_with("warning: ") {
-        LineType::Warning
-      } else if line.trim().starts_with("Running ") {
-        LineType::Running(line)
-      } else if line.ends_with("... ok") {
-        LineType::SingleTestOk
-      } else if line.ends_with("... FAILED") {
-        LineType::SingleTestFailed(line)
-      } else {
-        LineType::Unprocessed(line)
+      let trimmed = line.trim_start();
+      if trimmed.starts_with("left:") {
+        side = Some(Red)
+      } else if trimmed.starts_with("right:") {
+        side = Some(Green)
+      }
+
+      match side {
+        Some(colour) => colour.paint(line).to_string(),
+        None => Red.bold().paint(line).to_string(),
       }
     })
-    .collect()
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+
+/// Processes lines that are not compiler messages one at a time. Test successes are collected
+/// across lines so they can be printed as dots when the test run finishes.
+pub struct StdoutLines {
+  filtered_out: Vec<String>,
+  test_results_buffer: HashMap<&'static str, u32>,
+  /// Set while reading the failures section, which is left out as the failing tests are printed from their results
+  in_failures: bool,
+  /// Set from when a test binary starts running until it reports a result
+  awaiting_result: bool,
+}
+
+impl StdoutLines {
+
+  pub fn new(filtered_out: Vec<String>) -> Self {
+    Self {
+      filtered_out,
+      test_results_buffer: HashMap::new(),
+      in_failures: false,
+      awaiting_result: false,
+    }
+  }
+
+  pub fn print_stdout_line(&mut self, line: String) {
+    if let Some(output) = self.get_stdout_line(get_line_type(line)) {
+      println!("{}", output)
+    }
+  }
+
+  fn get_stdout_line(&mut self, line_type: LineType) -> Option<String> {
+    let test_results_buffer = &mut self.test_results_buffer;
+
+    if self.in_failures {
+      match line_type {
+        LineType::TestResultFailed(_) | LineType::TestResultOk(_) => self.in_failures = false,
+        _ => return None,
+      }
+    }
+
+    match line_type {
+      LineType::Empty => None,
+      LineType::Failures => {
+        self.in_failures = true;
+        success_dots_string(test_results_buffer.get("success"))
+      },
+      LineType::TestResultFailed(line) => {
+        // Clear the test success
+        test_results_buffer.clear();
+        self.awaiting_result = false;
+        Some(test_failure_string(line.as_str()))
+      },
+      LineType::TestResultOk(line) => {
+        // Print out the collected tests
+        let dots = success_dots_string(test_results_buffer.get("success"));
+        let output = test_success_string(line.as_str(), dots.as_deref());
+        test_results_buffer.clear();
+        self.awaiting_result = false;
+        Some(output)
+      },
+      LineType::TestDots(line) => Some(test_run_dots_string(line.as_str())),
+      LineType::Finished => None,
+      LineType::Compiling => None,
+      LineType::Error => None,
+      LineType::Warning => None,
+      LineType::Running(line) => {
+        self.awaiting_result = true;
+        Some(test_name_string(line.as_str()))
+      },
+      // Only worth showing when the binary was killed before it could report a result
+      LineType::TestBinaryFailed(_) if !self.awaiting_result => None,
+      LineType::TestBinaryFailed(line) => {
+        let dots = success_dots_string(test_results_buffer.get("success"));
+        test_results_buffer.clear();
+        self.awaiting_result = false;
+        let failure = s!("{} {}", RGB(133, 138, 118).paint("stdout:"), Red.paint(line.trim()));
+        Some(dots.map_or(failure.clone(), |dots| s!("{}\n{}", dots, failure)))
+      },
+      LineType::SingleTestOk => {
+        *test_results_buffer.entry("success").or_insert(0) += 1;
+        None
+      },
+      // Named again when the failing tests are printed
+      LineType::SingleTestFailed => None,
+      LineType::Unprocessed(line) => default_stdout_string(line.as_str(), &self.filtered_out),
+    }
+  }
+}
+
+
+fn get_line_type(line: String) -> LineType {
+  if line.is_empty() {
+    LineType::Empty
+  } else if line == "failures:" {
+    LineType::Failures
+  } else if line.starts_with("test result: FAILED.") {
+    LineType::TestResultFailed(line)
+  } else if line.starts_with("test result: ok.") {
+    LineType::TestResultOk(line)
+  } else if line.split_inclusive('.').count() == line.len()  {
+    LineType::TestDots(line)
+  } else if line.trim().starts_with("Finished ") {
+    LineType::Finished
+  } else if line.trim().starts_with("Compiling ") {
+    LineType::Compiling
+  } else if line.contains("error: test failed, to rerun pass ") {
+    LineType::TestBinaryFailed(line)
+  } else if line.trim().starts_with("error: ") {
+    LineType::Error
+  } else if line.trim().starts_with("warning: ") {
+    LineType::Warning
+  } else if line.trim().starts_with("Running ") {
+    LineType::Running(line)
+  } else if line.ends_with("... ok") {
+    LineType::SingleTestOk
+  } else if line.ends_with("... FAILED") {
+    LineType::SingleTestFailed
+  } else {
+    LineType::Unprocessed(line)
+  }
 }
 
 
@@ -159,7 +571,7 @@ enum OutputType<'a> {
 
 enum LineType {
   Empty,
-  Failures(String),
+  Failures,
   TestResultFailed(String),
   TestResultOk(String),
   TestDots(String),
@@ -168,17 +580,13 @@ enum LineType {
   Error,
   Warning,
   Running(String),
+  TestBinaryFailed(String),
   SingleTestOk,
-  SingleTestFailed(String),
+  SingleTestFailed,
   Unprocessed(String),
 }
 
 
-fn failed_test_name_string(line: &str) -> String {
-  s!("{}", Red.paint(line))
-}
-
-
 fn test_name_string(line: &str) -> String {
   s!("\n{}", Yellow.paint(line.trim().strip_prefix("Running ").unwrap_or(line)))
 }
@@ -188,14 +596,6 @@ fn test_run_dots_string(line: &str) -> String {
   s!("{}", Green.paint(line))
 }
 
-fn failure_line_string(line: &str, maybe_dots: Option<&str>) -> String {
-  match maybe_dots {
-    Some(dots) => s!("{}\n{} {}", dots, RGB(133, 138, 118).paint("stdout:"), Red.paint(line)),
-    None => s!("{} {}", RGB(133, 138, 118).paint("stdout:"), Red.paint(line)),
-  }
-}
-
-
 fn test_failure_string(line: &str) -> String {
   let failure = s!("test result: {}.", Red.paint("FAILED"));
   let message = s!("{}{}", failure, line.strip_prefix("test result: FAILED.").unwrap_or(""));
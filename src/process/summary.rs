@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::format as s;
+
+use serde::Serialize;
+
+use crate::CompilerMessage;
+use crate::cargo_event::CargoEvent;
+use super::level_status::{LevelStatus, LevelType};
+
+
+/// Reported once all the input has been processed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Summary {
+  #[serde(flatten)]
+  pub level_status: LevelStatus,
+  pub counts: Counts,
+  /// The messages of any internal compiler errors, whether or not they were filtered out
+  pub internal_compiler_errors: Vec<String>,
+  pub build: BuildSummary,
+  /// The errors and warnings in each file, in the order the files were first seen
+  pub files: Vec<FileCounts>,
+}
+
+
+/// What happened to the messages of each level on their way through the pipeline.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Counts {
+  pub errors: LevelCounts,
+  pub warnings: LevelCounts,
+  pub notes: LevelCounts,
+}
+
+impl Counts {
+
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  pub fn for_level(&mut self, level_type: &LevelType) -> &mut LevelCounts {
+    match level_type {
+      LevelType::ErrorLevel(_)   => &mut self.errors,
+      LevelType::WarningLevel(_) => &mut self.warnings,
+      LevelType::NoteLevel(_)    => &mut self.notes,
+    }
+  }
+}
+
+
+/// Each message decoded is counted once as removed by a filter, as a duplicate, as over the limit
+/// or as shown.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LevelCounts {
+  pub decoded: usize,
+  /// Removed by --target or --target-kind
+  pub removed_by_target_filter: usize,
+  /// Removed by --file-filter, --exclude-file or --lines
+  pub removed_by_file_filter: usize,
+  /// Removed by --include-code or --exclude-code
+  pub removed_by_code_filter: usize,
+  /// Removed because the same message was already seen, such as when it is reported for several targets
+  pub duplicates: usize,
+  /// Not shown because of --items, --max-errors or --max-warnings, or because --show-warnings or --show-notes were not set
+  pub over_limit: usize,
+  pub shown: usize,
+}
+
+
+
+/// What Cargo reported about the build itself, rather than the code being compiled.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuildSummary {
+  /// Whether Cargo reported the build as successful. Not set if Cargo didn't finish the build
+  pub success: Option<bool>,
+  /// The targets in the workspace that were built, rather than found to be up to date. Example: quiet (bin)
+  pub built: Vec<String>,
+  /// The packages whose build scripts failed. Example: openssl-sys v0.9.102
+  pub build_script_failures: Vec<String>,
+  /// The packages in the workspace and any that failed to compile, in the order they were first seen
+  pub crates: Vec<CrateStatus>,
+}
+
+impl BuildSummary {
+
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  pub fn update(&mut self, cargo_event: &CargoEvent) {
+    match cargo_event {
+      CargoEvent::CompilerArtifact(artifact) => {
+        let target = &artifact.target;
+        // Build scripts are all named build-script-build, so they are left out
+        let is_build_script = target.kind.iter().any(|kind| kind == "custom-build");
+        if artifact.is_local() && !artifact.fresh && !is_build_script {
+          let built = s!("{} ({})", target.name, target.kind.join(", "));
+          if !self.built.contains(&built) {
+            self.built.push(built)
+          }
+        }
+
+        if artifact.is_local() && !is_build_script {
+          self.crate_status(artifact.package_name(), CrateBuildStatus::Compiled, None)
+        }
+      },
+      CargoEvent::BuildScriptExecuted(_) => (),
+      CargoEvent::BuildFinished(build_finished) => self.success = Some(build_finished.success),
+      CargoEvent::BuildScriptFailed(build_script_failed) => self.build_script_failures.push(build_script_failed.package.clone()),
+      CargoEvent::CouldNotCompile(could_not_compile) => {
+        self.crate_status(could_not_compile.package.clone(), CrateBuildStatus::Failed, could_not_compile.target.clone())
+      },
+    }
+  }
+
+  /// Cargo stops building once a crate fails to compile, so the members of the workspace that were
+  /// neither compiled nor failed were not attempted. The members are only added when at least one of
+  /// them was seen, so input from another workspace doesn't list the members of this one.
+  pub fn add_not_attempted(&mut self, workspace_members: &[String]) {
+    let any_failed = self.crates.iter().any(|crate_status| crate_status.status == CrateBuildStatus::Failed);
+    let any_member_seen = self.crates.iter().any(|crate_status| workspace_members.contains(&crate_status.name));
+
+    if any_failed && any_member_seen {
+      workspace_members
+        .iter()
+        .for_each(|member| self.crate_status(member.clone(), CrateBuildStatus::NotAttempted, None))
+    }
+  }
+
+  pub fn has_failed_crates(&self) -> bool {
+    self.crates.iter().any(|crate_status| crate_status.status == CrateBuildStatus::Failed)
+  }
+
+  /// Cargo reported that the build failed, whether or not it reported any errors.
+  pub fn failed(&self) -> bool {
+    self.success == Some(false) || self.has_failed_crates() || !self.build_script_failures.is_empty()
+  }
+
+  /// Cargo failed the build without reporting any errors as compiler messages, such as when a build
+  /// script or the linker fails.
+  pub fn failed_without_errors(&self, counts: &Counts) -> bool {
+    self.failed() && (counts.errors.decoded == 0 || !self.build_script_failures.is_empty())
+  }
+
+  /// A crate that failed stays failed, even if some of its targets compiled.
+  fn crate_status(&mut self, name: String, status: CrateBuildStatus, target: Option<String>) {
+    match self.crates.iter_mut().find(|crate_status| crate_status.name == name) {
+      Some(crate_status) => {
+        if status == CrateBuildStatus::Failed {
+          crate_status.status = status;
+          crate_status.failed_target = target
+        }
+      },
+      None => self.crates.push(CrateStatus { name, status, failed_target: target }),
+    }
+  }
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateStatus {
+  pub name: String,
+  pub status: CrateBuildStatus,
+  /// The target that failed to compile, when Cargo reports it. Example: bin "quiet"
+  pub failed_target: Option<String>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrateBuildStatus {
+  Compiled,
+  Failed,
+  NotAttempted,
+}
+
+impl CrateBuildStatus {
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CrateBuildStatus::Compiled     => "compiled",
+      CrateBuildStatus::Failed       => "failed",
+      CrateBuildStatus::NotAttempted => "not attempted",
+    }
+  }
+}
+
+
+/// The number of errors and warnings that made it through the filters for a file. Messages are
+/// counted against the file of their primary span, which is where rustc points to as the cause.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileCounts {
+  /// Not set for messages without a primary span, such as "aborting due to 2 previous errors"
+  pub file_name: Option<String>,
+  pub errors: usize,
+  pub warnings: usize,
+}
+
+
+#[derive(Debug, Default)]
+pub struct FileTable {
+  files: Vec<FileCounts>,
+  index: HashMap<Option<String>, usize>,
+}
+
+impl FileTable {
+
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Notes are not counted, as they usually belong to an error.
+  pub fn update(&mut self, level_type: &LevelType) {
+    let compiler_message =
+      match level_type {
+        LevelType::ErrorLevel(cm)   => cm,
+        LevelType::WarningLevel(cm) => cm,
+        LevelType::NoteLevel(_)     => return,
+      };
+
+    let file_name = primary_file_name(compiler_message);
+    let index =
+      match self.index.get(&file_name) {
+        Some(index) => *index,
+        None => {
+          self.files.push(FileCounts { file_name: file_name.clone(), ..Default::default() });
+          self.index.insert(file_name, self.files.len() - 1);
+          self.files.len() - 1
+        },
+      };
+
+    let file_counts = &mut self.files[index];
+    match level_type {
+      LevelType::ErrorLevel(_)   => file_counts.errors += 1,
+      LevelType::WarningLevel(_) => file_counts.warnings += 1,
+      LevelType::NoteLevel(_)    => (),
+    }
+  }
+
+  pub fn files(self) -> Vec<FileCounts> {
+    self.files
+  }
+}
+
+
+pub fn primary_file_name(compiler_message: &CompilerMessage) -> Option<String> {
+  compiler_message
+    .message
+    .spans
+    .iter()
+    .find(|span| span.is_primary)
+    .map(|span| span.file_name.clone())
+}
@@ -0,0 +1,628 @@
+use std::format as s;
+
+use serde::Serialize;
+
+use crate::test_event::{SuiteEvent, TestCaseEvent, TestEvent};
+use super::nextest;
+
+/// Cargo reports that a test binary failed with: error: test failed, to rerun pass `--lib`
+const TEST_BINARY_FAILED: &str = "error: test failed, to rerun pass ";
+
+/// The results of running a test binary, taken from libtest's output. The summary line libtest
+/// prints at the end of each binary provides the counts, while the tests come from the lines
+/// before it.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TestResult {
+  /// Taken from Cargo's Running or Doc-tests line. Example: unittests src/lib.rs (target/debug/deps/quiet-0a1b2c3d)
+  pub binary: Option<String>,
+  pub success: bool,
+  pub passed: u32,
+  pub failed: u32,
+  pub ignored: u32,
+  pub measured: u32,
+  pub filtered_out: u32,
+  pub duration: Option<String>,
+  pub failed_tests: Vec<String>,
+  /// The tests libtest reported by name. With --format terse passing tests are only shown as dots,
+  /// so only the failed tests are known
+  pub tests: Vec<TestCase>,
+  /// Set when the result was read from libtest's text output, which is printed as it is read. Results
+  /// read from libtest's JSON events or from cargo-nextest are printed from the result instead
+  #[serde(skip)]
+  pub printed_as_read: bool,
+}
+
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TestCase {
+  pub name: String,
+  pub status: TestStatus,
+  /// Only reported when tests are run with --report-time. Example: 0.012s
+  pub duration: Option<String>,
+  /// What the test printed before it finished. Only shown by libtest for failed tests
+  pub stdout: Vec<String>,
+  pub panic: Option<Panic>,
+}
+
+impl TestCase {
+
+  fn new(name: &str, status: TestStatus) -> Self {
+    Self {
+      name: name.to_owned(),
+      status,
+      duration: None,
+      stdout: vec![],
+      panic: None,
+    }
+  }
+}
+
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestStatus {
+  Passed,
+  Failed,
+  Ignored,
+}
+
+
+/// Where and why a test panicked.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct Panic {
+  /// Can span several lines, such as the left and right values of a failed assert_eq
+  pub message: String,
+  pub location: Option<PanicLocation>,
+}
+
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PanicLocation {
+  pub file_name: String,
+  pub line: usize,
+  pub column: usize,
+}
+
+
+/// The part of a test binary's output being read.
+#[derive(Debug, Default)]
+enum Section {
+  /// The list of tests as they are run
+  #[default]
+  Tests,
+  /// The output captured for a failed test, following ---- some_test stdout ----
+  Captured(usize),
+  /// The panic message of a failed test, which follows its captured output
+  PanicMessage(usize, PanicFormat),
+  /// The list of failed test names that follows the captured output
+  FailureList,
+}
+
+
+#[derive(Debug, Clone, Copy)]
+enum PanicFormat {
+  /// thread 'some_test' panicked at src/lib.rs:17:5:
+  /// the message
+  MessageAfterLocation,
+  /// thread 'some_test' panicked at 'the message', src/lib.rs:17:5
+  MessageBeforeLocation,
+}
+
+
+/// The state of a cargo-nextest run, which reports the tests of all binaries as a single run.
+#[derive(Debug, Default)]
+struct NextestRun {
+  /// Set once the summary has been read. The failed tests are listed again after it
+  summarised: bool,
+}
+
+
+/// Collects test results from the output of libtest or cargo-nextest one line at a time.
+#[derive(Debug, Default)]
+pub struct TestResults {
+  binary: Option<String>,
+  tests: Vec<TestCase>,
+  section: Section,
+  failures: bool,
+  nextest: Option<NextestRun>,
+  /// Set when the captured output includes the test harness's own output, as it does for cargo-nextest
+  harness_in_captured: bool,
+  /// Newer versions of cargo-nextest start with a separator, which is held back until it is known
+  /// whether it starts a run
+  held_separator: Option<String>,
+}
+
+impl TestResults {
+
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Returns a result when the line completes the run of a test binary, or the run of cargo-nextest.
+  pub fn process_line(&mut self, line: &str) -> Option<TestResult> {
+    if self.nextest.is_some() {
+      return self.process_nextest_line(line)
+    }
+
+    if nextest::is_start_line(line) {
+      self.nextest = Some(NextestRun::default());
+      self.harness_in_captured = true;
+      self.tests.clear();
+      self.section = Section::Tests;
+      return None
+    }
+
+    if let Some(result) = parse_test_result(line) {
+      return Some(self.finish(TestResult { printed_as_read: true, ..result }))
+    }
+
+    if let Some((before, _)) = line.split_once(TEST_BINARY_FAILED) {
+      return self.test_binary_failed(before)
+    }
+
+    if let Some(binary) = binary_name(line) {
+      self.binary = Some(binary.to_owned());
+      self.tests.clear();
+      self.section = Section::Tests;
+    } else if let Some(test_name) = captured_test_name(line) {
+      let index = self.test_index(test_name, TestStatus::Failed);
+      self.section = Section::Captured(index)
+    } else if line == "failures:" {
+      // libtest prints this before the captured output and again before the list of failed tests
+      self.section =
+        match self.section {
+          Section::Tests => Section::Tests,
+          _              => Section::FailureList,
+        }
+    } else {
+      match self.section {
+        Section::Tests => self.process_test_line(line),
+        Section::Captured(index) => self.process_captured_line(index, line),
+        Section::PanicMessage(index, format) => self.process_panic_line(index, format, line),
+        Section::FailureList => (),
+      }
+    }
+
+    None
+  }
+
+  /// Returns a result when the event completes the run of a test binary. The name of the binary
+  /// still comes from Cargo's text output.
+  pub fn process_event(&mut self, test_event: TestEvent) -> Option<TestResult> {
+    match test_event {
+      TestEvent::Suite(suite) if suite.event == "started" => {
+        self.tests.clear();
+        None
+      },
+      TestEvent::Suite(suite) => Some(self.finish(suite_result(suite))),
+      TestEvent::Test(test) => {
+        self.process_test_event(test);
+        None
+      },
+    }
+  }
+
+  /// Whether any of the test runs seen so far have failed.
+  pub fn has_failures(&self) -> bool {
+    self.failures
+  }
+
+  /// The lines to show as they are read, once the line has been processed. cargo-nextest's output
+  /// is left out, as it is shown from its result instead.
+  pub fn shown_lines(&mut self, line: String) -> Vec<String> {
+    if self.nextest.is_some() {
+      self.held_separator = None;
+      return vec![]
+    }
+
+    if nextest::is_separator(&line) {
+      return self.held_separator.replace(line).into_iter().collect()
+    }
+
+    self.held_separator.take().into_iter().chain(std::iter::once(line)).collect()
+  }
+
+  /// A separator that was held back at the end of the input, which didn't start a run.
+  pub fn finish_lines(&mut self) -> Option<String> {
+    self.held_separator.take()
+  }
+
+  fn process_nextest_line(&mut self, line: &str) -> Option<TestResult> {
+    let summarised = self.nextest.as_ref().is_some_and(|run| run.summarised);
+
+    if let Some(status_line) = nextest::parse_status_line(line) {
+      // The failed tests listed after the summary have already been counted
+      if !summarised {
+        let index = self.test_index(&status_line.name, status_line.status);
+        self.tests[index].status = status_line.status;
+        self.tests[index].duration = Some(status_line.duration);
+      }
+      self.section = Section::Tests;
+      None
+    } else if summarised {
+      self.nextest = None;
+      self.process_line(line)
+    } else if let Some(summary) = nextest::parse_summary_line(line) {
+      let result =
+        TestResult {
+          success: summary.failed == 0,
+          passed: summary.passed,
+          failed: summary.failed,
+          ignored: summary.skipped,
+          duration: Some(summary.duration),
+          ..Default::default()
+        };
+
+      self.nextest = Some(NextestRun { summarised: true });
+      Some(self.finish(result))
+    } else if let Some(name) = nextest::parse_captured_header(line) {
+      let index = self.test_index(&name, TestStatus::Failed);
+      self.section = Section::Captured(index);
+      None
+    } else if nextest::is_separator(line) || nextest::is_progress_line(line) {
+      self.section = Section::Tests;
+      None
+    } else {
+      self.process_captured(line);
+      None
+    }
+  }
+
+  /// Reads the output captured for a failed test. cargo-nextest runs each test on its own, so the
+  /// output of the test harness around it is left out.
+  fn process_captured(&mut self, line: &str) {
+    let index =
+      match self.section {
+        Section::Captured(index) | Section::PanicMessage(index, _) => index,
+        _ => return,
+      };
+
+    if self.harness_in_captured {
+      let test_name = &self.tests[index].name;
+      // cargo-nextest prefixes the test name with the binary id. Example: failing-tests::api api_fails
+      let harness_test_name = test_name.split_once(' ').map_or(test_name.as_str(), |(_, name)| name);
+      if nextest::is_harness_line(line, harness_test_name) {
+        return
+      }
+    }
+
+    match self.section {
+      Section::Captured(index) => self.process_captured_line(index, line),
+      Section::PanicMessage(index, format) => self.process_panic_line(index, format, line),
+      _ => (),
+    }
+  }
+
+  /// A test binary that was killed, such as by a signal, never reports a result, so one is made up
+  /// from the tests it reported. Cargo reports the failure on the same line as the test that was
+  /// running, if there was only one:
+  /// test tests::crashes ... error: test failed, to rerun pass `--lib`
+  fn test_binary_failed(&mut self, before: &str) -> Option<TestResult> {
+    self.failures = true;
+
+    if let Some(name) = before.strip_prefix("test ").and_then(|rest| rest.strip_suffix(" ... ")) {
+      let index = self.test_index(name, TestStatus::Failed);
+      self.tests[index].status = TestStatus::Failed;
+    }
+
+    // The binaries that reported a result have already been finished
+    self.binary.as_ref()?;
+
+    let count = |status: TestStatus| self.tests.iter().filter(|test| test.status == status).count() as u32;
+    let result =
+      TestResult {
+        success: false,
+        passed: count(TestStatus::Passed),
+        failed: count(TestStatus::Failed),
+        ignored: count(TestStatus::Ignored),
+        printed_as_read: true,
+        ..Default::default()
+      };
+
+    Some(self.finish(result))
+  }
+
+  fn finish(&mut self, mut result: TestResult) -> TestResult {
+    result.binary = self.binary.take();
+    result.tests = std::mem::take(&mut self.tests).into_iter().map(trim_captured).collect();
+    result.failed_tests =
+      result
+        .tests
+        .iter()
+        .filter(|test| test.status == TestStatus::Failed)
+        .map(|test| test.name.clone())
+        .collect();
+    self.section = Section::Tests;
+    self.harness_in_captured = false;
+    self.failures |= !result.success;
+    result
+  }
+
+  fn process_test_event(&mut self, test: TestCaseEvent) {
+    let status =
+      match test.event.as_str() {
+        "ok"      => TestStatus::Passed,
+        "failed"  => TestStatus::Failed,
+        "ignored" => TestStatus::Ignored,
+        // Such as started, or timeout when a test has been running for a long time
+        _         => return,
+      };
+
+    // cargo-nextest's libtest-json-plus format prefixes the test name with the binary id. Example: failing-tests::api$api_fails
+    let name =
+      match test.name.split_once('$') {
+        Some((binary_id, name)) => {
+          self.binary.get_or_insert_with(|| binary_id.to_owned());
+          self.harness_in_captured = true;
+          s!("{} {}", binary_id, name)
+        },
+        None => test.name,
+      };
+
+    let index = self.test_index(&name, status);
+    self.tests[index].status = status;
+    self.tests[index].duration = test.exec_time.map(|seconds| s!("{:.3}s", seconds));
+
+    // The captured output is read the same way as libtest's text output
+    self.section = Section::Captured(index);
+    test
+      .stdout
+      .iter()
+      .flat_map(|stdout| stdout.lines())
+      .for_each(|line| self.process_captured(line));
+    self.section = Section::Tests;
+  }
+
+  /// Parses lines such as:
+  /// test some_test ... ok
+  /// test some_test ... FAILED
+  /// test some_test ... ignored, too slow
+  /// test some_test ... ok <0.012s>
+  fn process_test_line(&mut self, line: &str) {
+    let Some((name, outcome)) = line.strip_prefix("test ").and_then(|rest| rest.split_once(" ... ")) else { return };
+
+    let (outcome, duration) =
+      match outcome.trim_end().strip_suffix('>').and_then(|rest| rest.rsplit_once(" <")) {
+        Some((outcome, duration)) => (outcome, Some(duration.to_owned())),
+        None => (outcome, None),
+      };
+
+    let status =
+      if outcome == "ok" {
+        TestStatus::Passed
+      } else if outcome == "FAILED" {
+        TestStatus::Failed
+      } else if outcome.starts_with("ignored") {
+        TestStatus::Ignored
+      } else {
+        return
+      };
+
+    let index = self.test_index(name, status);
+    let test = &mut self.tests[index];
+    test.status = status;
+    test.duration = duration;
+  }
+
+  fn process_captured_line(&mut self, index: usize, line: &str) {
+    match parse_panic_line(line) {
+      Some((panic, format)) => {
+        self.tests[index].panic = Some(panic);
+        self.section = Section::PanicMessage(index, format);
+        // The message and location can both be on the panic line
+        if let PanicFormat::MessageBeforeLocation = format {
+          self.process_panic_line(index, format, "")
+        }
+      },
+      None => self.tests[index].stdout.push(line.to_owned()),
+    }
+  }
+
+  fn process_panic_line(&mut self, index: usize, format: PanicFormat, line: &str) {
+    let Some(panic) = self.tests[index].panic.as_mut() else { return };
+
+    match format {
+      PanicFormat::MessageAfterLocation => {
+        if ends_panic(line) {
+          self.section = Section::FailureList
+        } else {
+          push_line(&mut panic.message, line)
+        }
+      },
+      PanicFormat::MessageBeforeLocation => {
+        if panic.location.is_none() {
+          if !line.is_empty() {
+            push_line(&mut panic.message, line)
+          }
+          if let Some((message, location)) = split_message_and_location(&panic.message) {
+            panic.message = message;
+            panic.location = Some(location);
+          }
+        } else if ends_panic(line) {
+          self.section = Section::FailureList
+        }
+      },
+    }
+  }
+
+  /// Tests are reported once when they run and again when their captured output is shown.
+  fn test_index(&mut self, name: &str, status: TestStatus) -> usize {
+    match self.tests.iter().position(|test| test.name == name) {
+      Some(index) => index,
+      None => {
+        self.tests.push(TestCase::new(name, status));
+        self.tests.len() - 1
+      },
+    }
+  }
+}
+
+
+/// Rust prints a note on how to see the backtrace after the panic message, or the backtrace itself.
+/// Newer versions of cargo-nextest indent them.
+fn ends_panic(line: &str) -> bool {
+  let line = line.trim();
+  line.starts_with("note: run with `RUST_BACKTRACE") || line == "stack backtrace:"
+}
+
+
+/// Removes the indentation all the lines share, such as when cargo-nextest indents the captured
+/// output, while keeping the indentation of the left and right values of a failed assertion.
+fn remove_common_indentation(text: &str) -> String {
+  let indentation =
+    text
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .map(|line| line.len() - line.trim_start().len())
+      .min()
+      .unwrap_or(0);
+
+  text
+    .lines()
+    .map(|line| line.get(indentation..).unwrap_or(line.trim_start()))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+
+/// Blank lines separate the captured output and the panic message from whatever follows them.
+fn trim_captured(mut test: TestCase) -> TestCase {
+  while test.stdout.last().is_some_and(|line| line.trim().is_empty()) {
+    test.stdout.pop();
+  }
+
+  let leading_blank_lines = test.stdout.iter().take_while(|line| line.trim().is_empty()).count();
+  test.stdout.drain(..leading_blank_lines);
+
+  if let Some(panic) = test.panic.as_mut() {
+    panic.message = remove_common_indentation(panic.message.trim_end())
+  }
+
+  test
+}
+
+
+fn push_line(text: &mut String, line: &str) {
+  if !text.is_empty() {
+    text.push('\n')
+  }
+  text.push_str(line)
+}
+
+
+/// Cargo prints which test binary it is about to run with either of:
+///      Running unittests src/lib.rs (target/debug/deps/quiet-0a1b2c3d)
+///    Doc-tests quiet
+fn binary_name(line: &str) -> Option<&str> {
+  let line = line.trim();
+  line
+    .strip_prefix("Running ")
+    .or_else(|| line.strip_prefix("Doc-tests ").map(|_| line))
+}
+
+
+/// ---- some_test stdout ----
+fn captured_test_name(line: &str) -> Option<&str> {
+  line
+    .strip_prefix("---- ")
+    .and_then(|rest| rest.strip_suffix(" stdout ----"))
+}
+
+
+/// Parses the line a test's panic starts with, which depends on the version of Rust:
+/// thread 'some_test' panicked at src/lib.rs:17:5:
+/// thread 'some_test' (1239) panicked at src/lib.rs:17:5:
+/// thread 'some_test' panicked at 'the message', src/lib.rs:17:5
+fn parse_panic_line(line: &str) -> Option<(Panic, PanicFormat)> {
+  // Newer versions of cargo-nextest indent the captured output
+  let line = line.trim_start();
+  if !line.starts_with("thread '") {
+    return None
+  }
+
+  let (_, rest) = line.split_once(" panicked at ")?;
+
+  match rest.strip_prefix('\'') {
+    Some(message) => {
+      let panic = Panic { message: message.to_owned(), location: None };
+      Some((panic, PanicFormat::MessageBeforeLocation))
+    },
+    None => {
+      let location = parse_location(rest.strip_suffix(':').unwrap_or(rest));
+      Some((Panic { message: String::new(), location }, PanicFormat::MessageAfterLocation))
+    },
+  }
+}
+
+
+/// The old panic format ends the message with the location: the message', src/lib.rs:17:5
+fn split_message_and_location(message: &str) -> Option<(String, PanicLocation)> {
+  let (message, location) = message.rsplit_once("', ")?;
+  parse_location(location).map(|location| (message.to_owned(), location))
+}
+
+
+/// Parses a location such as src/lib.rs:17:5
+fn parse_location(location: &str) -> Option<PanicLocation> {
+  let mut parts = location.trim().rsplitn(3, ':');
+  let column = parts.next()?.parse().ok()?;
+  let line = parts.next()?.parse().ok()?;
+  let file_name = parts.next()?.to_owned();
+
+  Some(PanicLocation { file_name, line, column })
+}
+
+
+fn suite_result(suite: SuiteEvent) -> TestResult {
+  TestResult {
+    success: suite.event == "ok",
+    passed: suite.passed,
+    failed: suite.failed,
+    ignored: suite.ignored,
+    measured: suite.measured,
+    filtered_out: suite.filtered_out,
+    duration: suite.exec_time.map(|seconds| s!("{:.2}s", seconds)),
+    ..Default::default()
+  }
+}
+
+
+/// Parses lines such as:
+/// test result: ok. 49 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.04s
+fn parse_test_result(line: &str) -> Option<TestResult> {
+  let (success, counts) =
+    if let Some(rest) = line.strip_prefix("test result: ok.") {
+      (true, rest)
+    } else if let Some(rest) = line.strip_prefix("test result: FAILED.") {
+      (false, rest)
+    } else {
+      return None
+    };
+
+  let mut result =
+    TestResult {
+      success,
+      ..Default::default()
+    };
+
+  counts
+    .split(';')
+    .map(str::trim)
+    .for_each(|part| {
+      if let Some(duration) = part.strip_prefix("finished in ") {
+        result.duration = Some(duration.to_owned())
+      } else if let Some((count, label)) = part.split_once(' ') {
+        let count = count.parse().unwrap_or(0);
+        match label {
+          "passed"       => result.passed = count,
+          "failed"       => result.failed = count,
+          "ignored"      => result.ignored = count,
+          "measured"     => result.measured = count,
+          "filtered out" => result.filtered_out = count,
+          _              => (),
+        }
+      }
+    });
+
+  Some(result)
+}
@@ -0,0 +1,125 @@
+use std::io::{BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::format as s;
+
+use crate::cli::Cli;
+use crate::process::compiler_messages::{get_matches_from, lossy_lines};
+use crate::process::outcome::Outcome;
+use crate::process::pipeline::run_pipeline;
+
+const MESSAGE_FORMAT: &str = "--message-format=json-diagnostic-rendered-ansi";
+/// cargo-nextest uses --message-format for the format of its test results
+const NEXTEST_MESSAGE_FORMAT: &str = "--cargo-message-format=json-diagnostic-rendered-ansi";
+/// Cargo's global options whose value can be supplied as the next argument
+const GLOBAL_OPTIONS_WITH_VALUES: [&str; 4] = ["-Z", "-C", "--config", "--color"];
+
+
+/// Runs Cargo with the message format quiet needs, feeding its merged stdout and stderr through
+/// the pipeline as it is produced. Returns the outcome of the run along with Cargo's exit status.
+pub fn run_cargo(cli: &Cli, cargo_args: &[String]) -> Result<(Outcome, ExitStatus), String> {
+  let mut child = spawn_cargo(cargo_args, false)?;
+  let lines = merged_lines(&mut child);
+
+  let outcome = run_pipeline(cli, get_matches_from(lines.into_iter()));
+
+  child
+    .wait()
+    .map(|status| (outcome, status))
+    .map_err(|e| s!("Could not wait for cargo to finish: {}", e))
+}
+
+
+/// With own_process_group, Cargo and everything it starts, such as rustc and the test binaries, can
+/// be killed together. It also means they no longer get the signals sent by pressing Ctrl-C.
+pub fn spawn_cargo(cargo_args: &[String], own_process_group: bool) -> Result<Child, String> {
+  let mut command = Command::new("cargo");
+  command
+    .args(cargo_arguments(cargo_args))
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+
+  #[cfg(unix)]
+  if own_process_group {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+  }
+
+  command
+    .spawn()
+    .map_err(|e| s!("Could not run cargo {}: {}", cargo_args.join(" "), e))
+}
+
+
+/// Interleaves the stdout and stderr of the child, line by line, in the order they are produced.
+/// The channel is closed when both streams are closed.
+pub fn merged_lines(child: &mut Child) -> Receiver<String> {
+  let (sender, receiver) = mpsc::channel();
+
+  if let Some(stdout) = child.stdout.take() {
+    forward_lines(stdout, sender.clone())
+  }
+
+  if let Some(stderr) = child.stderr.take() {
+    forward_lines(stderr, sender)
+  }
+
+  receiver
+}
+
+
+/// The stream is read until it is closed, even once nobody is listening, so Cargo and the test
+/// binaries it runs never write to a closed pipe.
+fn forward_lines<R: Read + Send + 'static>(source: R, sender: Sender<String>) {
+  thread::spawn(move || {
+    lossy_lines(BufReader::new(source))
+      .for_each(|line| {
+        let _ = sender.send(line);
+      })
+  });
+}
+
+
+/// The message format has to come before any `--` the user supplies, as everything after it is
+/// passed to the test binaries, so it is placed straight after the Cargo subcommand. For
+/// cargo-nextest it is placed after nextest's own subcommand. Example: nextest run
+fn cargo_arguments(cargo_args: &[String]) -> Vec<String> {
+  let Some(position) = subcommand_position(cargo_args) else {
+    let mut args = cargo_args.to_vec();
+    args.push(MESSAGE_FORMAT.to_owned());
+    return args
+  };
+
+  let (before, rest) = cargo_args.split_at(position + 1);
+  let subcommand = &cargo_args[position];
+
+  let mut args = before.to_vec();
+  if subcommand == "nextest" && !rest.is_empty() {
+    args.push(rest[0].to_owned());
+    args.push(NEXTEST_MESSAGE_FORMAT.to_owned());
+    args.extend(rest[1..].iter().cloned());
+  } else {
+    args.push(MESSAGE_FORMAT.to_owned());
+    args.extend(rest.iter().cloned());
+  }
+  args
+}
+
+
+/// The subcommand can be preceded by a toolchain and Cargo's global options. Example: +nightly --offline -Z unstable-options test
+fn subcommand_position(cargo_args: &[String]) -> Option<usize> {
+  let mut position = 0;
+  while let Some(arg) = cargo_args.get(position) {
+    if GLOBAL_OPTIONS_WITH_VALUES.contains(&arg.as_str()) {
+      position += 2
+    } else if arg.starts_with('+') || arg.starts_with('-') {
+      position += 1
+    } else {
+      return Some(position)
+    }
+  }
+
+  None
+}
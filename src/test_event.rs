@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+
+/// The messages libtest reports when tests are run with --format json. Example:
+/// { "type": "test", "name": "tests::adds_numbers", "event": "ok", "exec_time": 0.000001066 }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum TestEvent {
+  Suite(SuiteEvent),
+  Test(TestCaseEvent),
+}
+
+
+/// Reported when a test binary starts running and once all its tests have finished.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SuiteEvent {
+  /// Example: started, ok or failed
+  pub event: String,
+  #[serde(default)]
+  pub passed: u32,
+  #[serde(default)]
+  pub failed: u32,
+  #[serde(default)]
+  pub ignored: u32,
+  #[serde(default)]
+  pub measured: u32,
+  #[serde(default)]
+  pub filtered_out: u32,
+  /// In seconds
+  pub exec_time: Option<f64>,
+}
+
+
+/// Reported when a test starts running and again when it finishes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestCaseEvent {
+  pub name: String,
+  /// Example: started, ok, failed, ignored or timeout
+  pub event: String,
+  /// In seconds. Only reported when tests are run with --report-time
+  pub exec_time: Option<f64>,
+  /// The output captured for a failed test, including where it panicked
+  pub stdout: Option<String>,
+}
+
+
+/// Used to tell libtest's messages apart from Cargo's, before decoding the whole message.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestEventType {
+  #[serde(rename = "type")]
+  pub event_type: String,
+}
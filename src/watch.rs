@@ -0,0 +1,173 @@
+use std::path::Path;
+use std::process::Child;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use std::format as s;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::process::compiler_messages::get_matches_from;
+use crate::process::pipeline::run_pipeline;
+use crate::process::stdout::{clear_screen, print_start_banner};
+use crate::runner::{merged_lines, spawn_cargo};
+
+const WATCHED_DIRECTORIES: [&str; 2] = ["src", "tests"];
+
+/// Watched through the project directory, as editors that save by renaming a new file over the old
+/// one end a watch on the file itself.
+const MANIFEST: &str = "Cargo.toml";
+
+/// How often a run checks whether it has been cancelled while waiting on Cargo's output.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+
+/// Runs Cargo every time a source file changes. A run that is still going when a new change
+/// arrives is cancelled, so the output on screen is always for the latest change.
+pub fn watch(cli: &Cli, cargo_args: &[String], debounce: Duration) -> Result<(), String> {
+  let (change_sender, change_receiver) = mpsc::channel();
+  let project_directory = std::env::current_dir().map_err(|e| s!("Could not get the current directory: {}", e))?;
+
+  let watcher_directory = project_directory.clone();
+  let mut watcher =
+    notify::recommended_watcher(move |event_result: notify::Result<Event>| {
+      if let Ok(event) = event_result {
+        if is_change(&event.kind) && event.paths.iter().any(|path| is_watched(&watcher_directory, path)) {
+          // The receiver only goes away when quiet is shutting down.
+          let _ = change_sender.send(());
+        }
+      }
+    })
+    .map_err(|e| s!("Could not create file watcher: {}", e))?;
+
+  WATCHED_DIRECTORIES
+    .iter()
+    .map(|directory| (project_directory.join(directory), RecursiveMode::Recursive))
+    .chain(std::iter::once((project_directory.clone(), RecursiveMode::NonRecursive)))
+    .filter(|(path, _)| path.exists())
+    .try_for_each(|(path, recursive_mode)| {
+      watcher
+        .watch(&path, recursive_mode)
+        .map_err(|e| s!("Could not watch {}: {}", path.to_string_lossy(), e))
+    })?;
+
+  loop {
+    if cli.output == OutputFormat::Text {
+      clear_screen();
+      print_start_banner();
+    }
+
+    let cargo_run = CargoRun::start(cli, cargo_args)?;
+    wait_for_change(&change_receiver, debounce)?;
+    cargo_run.cancel();
+  }
+}
+
+
+/// A single Cargo execution, with its output being processed on a separate thread.
+struct CargoRun {
+  child: Child,
+  cancelled: Arc<AtomicBool>,
+  pipeline: JoinHandle<()>,
+}
+
+impl CargoRun {
+
+  fn start(cli: &Cli, cargo_args: &[String]) -> Result<Self, String> {
+    let mut child = spawn_cargo(cargo_args, true)?;
+    let lines = merged_lines(&mut child);
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let pipeline_cancelled = cancelled.clone();
+    let pipeline_cli = cli.clone();
+    let pipeline =
+      thread::spawn(move || {
+        // Stop as soon as the run is cancelled, rather than processing what Cargo wrote before it was killed
+        let lines =
+          std::iter::from_fn(|| {
+            loop {
+              if pipeline_cancelled.load(Ordering::Relaxed) {
+                return None
+              }
+
+              match lines.recv_timeout(CANCEL_POLL_INTERVAL) {
+                Ok(line) => return Some(line),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+              }
+            }
+          });
+
+        run_pipeline(&pipeline_cli, get_matches_from(lines));
+      });
+
+    Ok(
+      Self {
+        child,
+        cancelled,
+        pipeline,
+      }
+    )
+  }
+
+  fn cancel(mut self) {
+    self.cancelled.store(true, Ordering::Relaxed);
+    // Cargo may have already finished, in which case there is nothing to kill.
+    kill_process_group(&self.child);
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+    let _ = self.pipeline.join();
+  }
+}
+
+
+/// Kills the processes Cargo started, such as rustc and test binaries that are still running, which
+/// share its process group. Killing Cargo alone would leave them running alongside the next run.
+#[cfg(unix)]
+fn kill_process_group(child: &Child) {
+  // Cargo was started in its own process group, whose id is Cargo's process id
+  if let Ok(process_group) = libc::pid_t::try_from(child.id()) {
+    unsafe {
+      libc::killpg(process_group, libc::SIGKILL);
+    }
+  }
+}
+
+
+#[cfg(not(unix))]
+fn kill_process_group(_child: &Child) {}
+
+
+/// Waits for a change and then for the changes to settle, so that saving several files at once
+/// results in a single run.
+fn wait_for_change(changes: &Receiver<()>, debounce: Duration) -> Result<(), String> {
+  changes
+    .recv()
+    .map_err(|_| "File watcher stopped unexpectedly".to_owned())?;
+
+  loop {
+    match changes.recv_timeout(debounce) {
+      Ok(_) => continue,
+      Err(RecvTimeoutError::Timeout) => return Ok(()),
+      Err(RecvTimeoutError::Disconnected) => return Err("File watcher stopped unexpectedly".to_owned()),
+    }
+  }
+}
+
+
+/// Reading files (which Cargo does on every run) also generates events, so only consider events
+/// that change the files.
+fn is_change(event_kind: &EventKind) -> bool {
+  matches!(event_kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+}
+
+
+/// The project directory is watched for changes to the manifest only, so that files such as
+/// Cargo.lock, which Cargo writes on every run, don't trigger another run.
+fn is_watched(project_directory: &Path, path: &Path) -> bool {
+  let relative = path.strip_prefix(project_directory).unwrap_or(path);
+  relative == Path::new(MANIFEST) || WATCHED_DIRECTORIES.iter().any(|directory| relative.starts_with(directory))
+}
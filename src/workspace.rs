@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use globset::GlobBuilder;
+use toml::Table;
+
+
+
+/// Finds the root of the workspace the current directory is in. This is the outermost directory
+/// with a Cargo.toml that has a workspace table, or failing that the nearest directory with a
+/// Cargo.toml.
+pub fn find_workspace_root() -> Option<PathBuf> {
+  let current_directory = std::env::current_dir().ok()?;
+
+  let manifest_directories: Vec<&Path> =
+    current_directory
+      .ancestors()
+      .filter(|directory| directory.join("Cargo.toml").is_file())
+      .collect();
+
+  manifest_directories
+    .iter()
+    .rev()
+    .find(|directory| {
+      read_manifest(directory)
+        .map(|manifest| manifest.contains_key("workspace"))
+        .unwrap_or(false)
+    })
+    .or(manifest_directories.first())
+    .map(|directory| directory.to_path_buf())
+}
+
+
+/// The names of the packages in the workspace the current directory is in, in the order they are
+/// listed in its Cargo.toml. Globs in `members` are only expanded to directories that exist.
+pub fn workspace_members() -> Vec<String> {
+  let Some(root) = find_workspace_root() else { return vec![] };
+  let Some(manifest) = read_manifest(&root) else { return vec![] };
+
+  let root_package = package_name(&manifest);
+
+  let workspace = manifest.get("workspace").and_then(|workspace| workspace.as_table());
+  let exclude = workspace.map(|workspace| string_array(workspace, "exclude")).unwrap_or_default();
+
+  let member_packages =
+    workspace
+      .map(|workspace| string_array(workspace, "members"))
+      .unwrap_or_default()
+      .into_iter()
+      .flat_map(|member| member_directories(&root, &member))
+      .filter(|directory| !exclude.iter().any(|excluded| directory == &root.join(excluded)))
+      .filter_map(|directory| read_manifest(&directory).and_then(|manifest| package_name(&manifest)));
+
+  let mut members: Vec<String> = vec![];
+  root_package
+    .into_iter()
+    .chain(member_packages)
+    .for_each(|name| {
+      if !members.contains(&name) {
+        members.push(name)
+      }
+    });
+
+  members
+}
+
+
+fn read_manifest(directory: &Path) -> Option<Table> {
+  std::fs::read_to_string(directory.join("Cargo.toml"))
+    .ok()
+    .and_then(|content| content.parse::<Table>().ok())
+}
+
+
+fn package_name(manifest: &Table) -> Option<String> {
+  manifest
+    .get("package")
+    .and_then(|package| package.get("name"))
+    .and_then(|name| name.as_str())
+    .map(|name| name.to_owned())
+}
+
+
+fn string_array(table: &Table, key: &str) -> Vec<String> {
+  table
+    .get(key)
+    .and_then(|value| value.as_array())
+    .map(|values| values.iter().filter_map(|value| value.as_str().map(|value| value.to_owned())).collect())
+    .unwrap_or_default()
+}
+
+
+/// Expands a member such as crates/* to the directories it matches, sorted by name.
+fn member_directories(root: &Path, member: &str) -> Vec<PathBuf> {
+  if !member.contains(['*', '?', '[', '{']) {
+    return vec![root.join(member)]
+  }
+
+  let Ok(glob) = GlobBuilder::new(member).literal_separator(true).build() else { return vec![] };
+  let matcher = glob.compile_matcher();
+  let depth = member.trim_end_matches('/').split('/').count();
+
+  let mut directories: Vec<PathBuf> =
+    sub_directories(root, depth)
+      .into_iter()
+      .filter(|directory| {
+        directory
+          .strip_prefix(root)
+          .map(|relative| matcher.is_match(relative))
+          .unwrap_or(false)
+      })
+      .collect();
+
+  directories.sort();
+  directories
+}
+
+
+/// The directories exactly depth levels below directory.
+fn sub_directories(directory: &Path, depth: usize) -> Vec<PathBuf> {
+  if depth == 0 {
+    return vec![directory.to_path_buf()]
+  }
+
+  std::fs::read_dir(directory)
+    .map(|entries| {
+      entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .flat_map(|path| sub_directories(&path, depth - 1))
+        .collect()
+    })
+    .unwrap_or_default()
+}
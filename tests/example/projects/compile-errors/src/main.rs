@@ -0,0 +1,5 @@
+fn main() {
+  let unused = 1;
+  let count: u32 = "one";
+  println!("{}", count);
+}
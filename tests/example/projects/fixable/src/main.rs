@@ -0,0 +1,5 @@
+fn main() {
+  let mut first = 1;
+  let mut second = 2;
+  println!("{} {}", first, second);
+}
@@ -1,7 +1,11 @@
 use assert_cmd::Command;
-use predicates::prelude::predicate;
+use predicates::prelude::{predicate, PredicateBooleanExt};
 use std::{println as p, format as s};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader, Write};
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::time::Duration;
 
 
 enum AssertionType<'a> {
@@ -17,7 +21,7 @@ fn no_errors_1() {
       AssertionType::Contains("quiet"),
       AssertionType::Contains("*** No compilation errors"),
     ];
-  run_quiet("no-errors-1.txt", &stdout_lines)
+  run_quiet("no-errors-1.txt", 0, &stdout_lines)
 }
 
 
@@ -28,7 +32,7 @@ fn no_errors_2() {
       AssertionType::Contains("quiet"),
       AssertionType::Contains("*** No compilation errors"),
     ];
-  run_quiet("no-errors-2.txt", &stdout_lines)
+  run_quiet("no-errors-2.txt", 0, &stdout_lines)
 }
 
 
@@ -39,7 +43,7 @@ fn no_errors_3() {
       AssertionType::Contains("quiet"),
       AssertionType::Contains("*** No compilation errors"),
     ];
-  run_quiet("no-errors-3.txt", &stdout_lines)
+  run_quiet("no-errors-3.txt", 0, &stdout_lines)
 }
 
 
@@ -50,7 +54,7 @@ fn no_errors_4() {
       AssertionType::Contains("quiet"),
       AssertionType::Contains("*** No compilation errors"),
     ];
-  run_quiet("no-errors-4.txt", &stdout_lines)
+  run_quiet("no-errors-4.txt", 0, &stdout_lines)
 }
 
 
@@ -67,7 +71,7 @@ fn errors_1() {
       AssertionType::DoesNotContain("--> src/github.rs:114:36"), // fourth error
       AssertionType::DoesNotContain("--> src/main.rs:56:32"),    // fifth error
     ];
-  run_quiet("errors-1.txt", &stdout_lines)
+  run_quiet("errors-1.txt", 1, &stdout_lines)
 }
 
 
@@ -86,7 +90,7 @@ fn no_errors_tests() {
       AssertionType::Contains("running 0 tests"),
       AssertionType::Contains("0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s"),
     ];
-  run_quiet("no-errors-tests.txt", &stdout_lines)
+  run_quiet("no-errors-tests.txt", 0, &stdout_lines)
 }
 
 
@@ -105,7 +109,7 @@ fn errors_tests() {
       AssertionType::Contains("runs_a_simple_template_with_shell_hook"),
       AssertionType::Contains("FAILED"),
     ];
-  run_quiet("errors-tests.txt", &stdout_lines)
+  run_quiet("errors-tests.txt", 2, &stdout_lines)
 }
 
 
@@ -119,11 +123,1212 @@ fn compilation_errors_tests() {
       AssertionType::Contains("tests/errors_integration_tests.rs:171:67"),
       AssertionType::Contains("expected `;`, found keyword `let`"),
     ];
-  run_quiet("compilation-errors-tests.txt", &stdout_lines)
+  run_quiet("compilation-errors-tests.txt", 1, &stdout_lines)
 }
 
 
-fn run_quiet<P: AsRef<Path>>(cargo_output_file: P, stdout_assertions: &[AssertionType]) {
+#[test]
+fn warnings_are_not_fatal_by_default() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("*** No compilation errors (but there are warnings) ***"),
+    ];
+  run_quiet("no-errors-4.txt", 0, &stdout_lines)
+}
+
+
+#[test]
+fn fatal_warnings() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("*** No compilation errors (but there are warnings) ***"),
+    ];
+  run_quiet_with_args("no-errors-4.txt", &["--items", "1", "--fatal-warnings"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn decoding_errors() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("Failed to decode CompilerMessage"),
+      AssertionType::Contains("*** No compilation errors"),
+    ];
+  run_quiet("decoding-errors.txt", 3, &stdout_lines)
+}
+
+
+#[test]
+fn json_output() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#"{"compiler_messages":[{"target":{"name":"purs""#),
+      AssertionType::Contains(r#""message":"cannot find type `PullRequest` in this scope""#),
+      AssertionType::Contains(r#""summary":{"errors":true,"warnings":false,"counts":{"errors":{"decoded":6,"removed_by_target_filter":0,"removed_by_file_filter":0,"removed_by_code_filter":0,"duplicates":0,"over_limit":5,"shown":1}"#),
+      AssertionType::DoesNotContain("---------- quiet"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--output", "json"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn test_results_per_binary() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#"{"type":"test-result","binary":"unittests src/lib.rs (target/debug/deps/failing_tests-52f320ff4e7b2f0c)","success":false,"passed":1,"failed":2,"ignored":1,"#),
+      AssertionType::Contains(r#"{"name":"tests::adds_numbers","status":"passed","duration":null,"stdout":[],"panic":null}"#),
+      AssertionType::Contains(r#"{"name":"tests::adds_wrong_numbers","status":"failed","duration":null,"stdout":["adding 2 and 2"],"panic":{"message":"assertion `left == right` failed\n  left: 4\n right: 5","location":{"file_name":"src/lib.rs","line":17,"column":5}}}"#),
+      AssertionType::Contains(r#"{"name":"tests::fails_with_message","status":"failed","duration":null,"stdout":[],"panic":{"message":"config should be loaded","location":{"file_name":"src/lib.rs","line":23,"column":12}}}"#),
+      AssertionType::Contains(r#"{"name":"tests::slow_test","status":"ignored","#),
+      AssertionType::Contains(r#""binary":"tests/api.rs (target/debug/deps/api-29dfec4556006549)","success":false,"passed":1,"failed":1,"#),
+      AssertionType::Contains(r#""binary":"Doc-tests failing_tests","success":true,"#),
+    ];
+  run_quiet_with_args("failing-tests.txt", &["--items", "1", "--output", "ndjson"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn failed_tests_are_shown_as_compact_blocks() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("---- tests::adds_wrong_numbers\u{1b}[0m at \u{1b}[34msrc/lib.rs:17:5"),
+      AssertionType::Contains("---- tests::fails_with_message\u{1b}[0m at \u{1b}[34msrc/lib.rs:23:12"),
+      AssertionType::Contains("config should be loaded"),
+      AssertionType::Contains("one and one should not be two"),
+      AssertionType::Contains("adding 2 and 2"),
+      AssertionType::DoesNotContain("failures:"),
+      AssertionType::DoesNotContain("thread 'tests::adds_wrong_numbers'"),
+      AssertionType::DoesNotContain("note: run with `RUST_BACKTRACE=1`"),
+    ];
+  run_quiet_with_args("failing-tests.txt", &["--items", "1"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn assertion_values_are_highlighted() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("\u{1b}[31m  left: 4\u{1b}[0m"),
+      AssertionType::Contains("\u{1b}[32m right: 5\u{1b}[0m"),
+    ];
+  run_quiet_with_args("failing-tests.txt", &["--items", "1"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn max_failures() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("---- tests::adds_wrong_numbers\u{1b}[0m at \u{1b}[34msrc/lib.rs:17:5"),
+      AssertionType::Contains("adding 2 and 2"),
+      AssertionType::Contains("src/lib.rs:17:5"),
+      AssertionType::Contains("*** 1 more failing test not shown ***"),
+      AssertionType::DoesNotContain("config should be loaded"),
+      AssertionType::DoesNotContain("one and one should not be two"),
+      AssertionType::DoesNotContain("failures:"),
+      AssertionType::DoesNotContain("... FAILED"),
+    ];
+  run_quiet_with_args("failing-tests.txt", &["--items", "1", "--max-failures", "1"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn test_binary_killed_by_a_signal() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("---- tests::crashes ----"),
+      AssertionType::Contains("test tests::crashes ... error: test failed, to rerun pass `--lib`"),
+      AssertionType::Contains("(signal: 6, SIGABRT: process abort signal)"),
+    ];
+  run_quiet("test-binary-killed.txt", 2, &stdout_lines)
+}
+
+
+#[test]
+fn test_binary_killed_by_a_signal_in_ndjson() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#"{"type":"test-result","binary":"unittests src/lib.rs (target/debug/deps/crashes-f57874efabc593c6)","success":false,"passed":1,"failed":1,"ignored":0,"measured":0,"filtered_out":0,"duration":null,"failed_tests":["tests::crashes"]"#),
+    ];
+  run_quiet_with_args("test-binary-killed.txt", &["--items", "1", "--output", "ndjson"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn test_results_from_libtest_json() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#"{"type":"test-result","binary":"unittests src/lib.rs (target/debug/deps/failing_tests-52f320ff4e7b2f0c)","success":false,"passed":1,"failed":2,"ignored":1,"#),
+      AssertionType::Contains(r#""failed_tests":["tests::adds_wrong_numbers","tests::fails_with_message"]"#),
+      AssertionType::Contains(r#"{"name":"tests::adds_wrong_numbers","status":"failed","duration":"0.000s","stdout":["adding 2 and 2"],"panic":{"message":"assertion `left == right` failed\n  left: 4\n right: 5","location":{"file_name":"src/lib.rs","line":17,"column":5}}}"#),
+      AssertionType::Contains(r#"{"name":"tests::slow_test","status":"ignored","#),
+      AssertionType::DoesNotContain("decoding-error"),
+    ];
+  run_quiet_with_args("failing-tests-json.txt", &["--items", "1", "--output", "ndjson"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn test_results_from_libtest_json_as_text() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("---- tests::adds_wrong_numbers\u{1b}[0m at \u{1b}[34msrc/lib.rs:17:5"),
+      AssertionType::Contains("config should be loaded"),
+      AssertionType::Contains("1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s"),
+      AssertionType::Contains("1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s"),
+      AssertionType::DoesNotContain("Failed to decode"),
+      AssertionType::DoesNotContain(r#"{ "type""#),
+    ];
+  run_quiet_with_args("failing-tests-json.txt", &["--items", "1"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn nextest() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("---- failing-tests tests::adds_wrong_numbers\u{1b}[0m at \u{1b}[34msrc/lib.rs:17:5"),
+      AssertionType::Contains("adding 2 and 2"),
+      AssertionType::Contains("config should be loaded"),
+      AssertionType::Contains("---- failing-tests::api api_crashes ----"),
+      AssertionType::Contains("3 passed; 3 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.160s"),
+      AssertionType::DoesNotContain("PASS ["),
+      AssertionType::DoesNotContain("SIGSEGV ["),
+      AssertionType::DoesNotContain("STDOUT:"),
+      AssertionType::DoesNotContain("running 1 test"),
+      AssertionType::DoesNotContain("------------"),
+    ];
+  run_quiet_with_args("nextest.txt", &["--items", "1"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn nextest_counts_from_summary() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("2 passed; 3 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.310s"),
+      AssertionType::Contains("stdout:\u{1b}[0m     exiting early\n"),
+      AssertionType::Contains("---- slow-tests tests::subtracts\u{1b}[0m at \u{1b}[34msrc/lib.rs:9:5"),
+      AssertionType::Contains("\n\u{1b}[1;31massertion failed: 2 - 1 == 0\u{1b}[0m\n"),
+      AssertionType::DoesNotContain("RUST_BACKTRACE"),
+      AssertionType::Contains("---- slow-tests tests::hangs ----"),
+      AssertionType::DoesNotContain("────────────"),
+      AssertionType::DoesNotContain("SLOW ["),
+      AssertionType::DoesNotContain("Cancelling"),
+    ];
+  run_quiet_with_args("nextest-timeout.txt", &["--items", "1"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn nextest_in_ndjson() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#""failed_tests":["failing-tests tests::adds_wrong_numbers","failing-tests tests::fails_with_message","failing-tests::api api_crashes"]"#),
+      AssertionType::Contains(r#"{"name":"failing-tests tests::adds_wrong_numbers","status":"failed","duration":"0.005s","stdout":["adding 2 and 2"],"panic":{"message":"assertion `left == right` failed\n  left: 4\n right: 5","location":{"file_name":"src/lib.rs","line":17,"column":5}}}"#),
+      AssertionType::Contains(r#"{"name":"failing-tests::api api_crashes","status":"failed","duration":"0.150s","stdout":[],"panic":null}"#),
+    ];
+  run_quiet_with_args("nextest.txt", &["--items", "1", "--output", "ndjson"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn nextest_libtest_json_plus() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#"{"type":"test-result","binary":"failing-tests","success":false,"passed":1,"failed":1,"ignored":1,"#),
+      AssertionType::Contains(r#"{"name":"failing-tests tests::adds_wrong_numbers","status":"failed","duration":"0.005s","stdout":["adding 2 and 2"],"panic":{"message":"assertion `left == right` failed\n  left: 4\n right: 5","#),
+      AssertionType::Contains(r#"{"type":"test-result","binary":"failing-tests::api","success":true,"passed":1,"#),
+      AssertionType::DoesNotContain("decoding-error"),
+    ];
+  run_quiet_with_args("nextest-libtest-json.txt", &["--items", "1", "--output", "ndjson"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn ndjson_output() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#"{"type":"test-result","binary":null,"success":true,"passed":49,"failed":0,"ignored":0,"measured":0,"filtered_out":0,"duration":"0.04s","failed_tests":[],"tests":[]}"#),
+      AssertionType::Contains(r#"{"type":"test-result","binary":null,"success":false,"passed":7,"failed":1,"ignored":0,"measured":0,"filtered_out":0,"duration":"0.01s","failed_tests":["runs_a_simple_template_with_shell_hook"],"tests":[{"name":"runs_a_simple_template_with_shell_hook","status":"failed","duration":null,"stdout":["target directory:"#),
+      AssertionType::Contains(r#"{"type":"summary","errors":false,"warnings":true,"#),
+      AssertionType::Contains(r#""warnings":{"decoded":21,"removed_by_target_filter":0,"removed_by_file_filter":0,"removed_by_code_filter":0,"duplicates":5,"over_limit":16,"shown":0}"#),
+      AssertionType::DoesNotContain("stdout:"),
+    ];
+  run_quiet_with_args("errors-tests.txt", &["--items", "1", "--output", "ndjson"], 2, &stdout_lines)
+}
+
+
+#[test]
+fn decodes_children_and_suggestions() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#""children":[{"rendered":null,"code":null,"level":"help","message":"a struct with a similar name exists""#),
+      AssertionType::Contains(r#""line_start":68,"line_end":68,"column_start":23,"column_end":34,"is_primary":true"#),
+      AssertionType::Contains(r#""suggested_replacement":"XPullRequest","suggestion_applicability":"MaybeIncorrect""#),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--output", "json"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn decodes_macro_expansions() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#""macro_decl_name":"add_one!""#),
+      AssertionType::Contains(r#""text":[{"text":"  let total: u32 = add_one!(1);","highlight_start":20,"highlight_end":31}]"#),
+      AssertionType::DoesNotContain(r#""decoding_errors":[{"#),
+    ];
+  run_quiet_with_args("macro-errors.txt", &["--items", "1", "--output", "json"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn exclude_code() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("cannot find struct, variant or union type `PullRequest` in this scope"),
+      AssertionType::DoesNotContain("cannot find type `PullRequest` in this scope"),
+      AssertionType::Contains("!!! There are compilation errors !!!"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--exclude-code", "E0412"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn include_code() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("unused import"),
+      AssertionType::DoesNotContain("is never used"),
+      AssertionType::DoesNotContain("is never read"),
+    ];
+  run_quiet_with_args("no-errors-4.txt", &["--items", "100", "--show-warnings", "--include-code", "E0308,unused_*"], 0, &stdout_lines)
+}
+
+
+#[test]
+fn file_filter_glob() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/github.rs:15:69"),
+      AssertionType::Contains("--> src/github.rs:88:23"),
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+      AssertionType::DoesNotContain("--> src/main.rs:56:32"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--file-filter", "src/git*.rs"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn file_filter_regex() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/github.rs:15:69"),
+      AssertionType::Contains("--> src/main.rs:56:32"),
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--file-filter", r"regex:^src/(main|github)\.rs$"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn file_filter_matches_whole_path_components() {
+  let stdout_lines =
+    [
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+      AssertionType::Contains("!!! There are compilation errors, but none of them match the filters !!!"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--file-filter", "odel.rs"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn errors_removed_by_filters_still_fail() {
+  let stdout_lines =
+    [
+      AssertionType::DoesNotContain("error["),
+      AssertionType::DoesNotContain("*** No compilation errors"),
+      AssertionType::Contains("!!! There are compilation errors, but none of them match the filters !!!"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--file-filter", "nonexistent.rs"], 1, &stdout_lines);
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--target", "nonexistent"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn multiple_file_filters() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/github.rs:15:69"),
+      AssertionType::Contains("--> src/main.rs:56:32"),
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--file-filter", "github.rs", "--file-filter", "main.rs"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn exclude_file() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/github.rs:15:69"),
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--exclude-file", "model.rs"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn file_filter_primary_span_only() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/model.rs:68:23"),
+      AssertionType::DoesNotContain("--> src/github.rs:15:69"), // Only has a secondary span in model.rs
+      AssertionType::DoesNotContain("--> src/main.rs:56:32"),   // Only has a secondary span in model.rs
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--file-filter", "model.rs", "--primary-span-only"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn file_filter_lines() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/github.rs:88:23"),
+      AssertionType::Contains("--> src/github.rs:114:36"),
+      AssertionType::DoesNotContain("--> src/github.rs:15:69"),
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "5", "--file-filter", "github.rs", "--lines", "80-120"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn invalid_lines() {
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .args(["--items", "1", "--lines", "200-120"])
+    .assert()
+    .code(4)
+    .stderr(predicate::str::contains("The start of the range (200) is after the end (120)"));
+}
+
+
+#[test]
+fn target_filter() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("unused variable: `unused_in_beta`"),
+      AssertionType::DoesNotContain("unused variable: `unused_in_lib`"),
+      AssertionType::DoesNotContain("unused variable: `unused_in_test`"),
+    ];
+  run_quiet_with_args("workspace-warnings.txt", &["--items", "10", "--show-warnings", "--target", "beta"], 0, &stdout_lines)
+}
+
+
+#[test]
+fn target_kind_filter() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("unused variable: `unused_in_test`"),
+      AssertionType::DoesNotContain("unused variable: `unused_in_lib`"),
+      AssertionType::DoesNotContain("unused variable: `unused_in_beta`"),
+    ];
+  run_quiet_with_args("workspace-warnings.txt", &["--items", "10", "--show-warnings", "--target-kind", "test"], 0, &stdout_lines)
+}
+
+
+#[test]
+fn max_errors() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/model.rs:68:23"),
+      AssertionType::Contains("--> src/github.rs:15:69"),
+      AssertionType::DoesNotContain("--> src/github.rs:88:23"),
+      AssertionType::Contains("*** Errors: 6 decoded, 4 over the limit, 2 shown ***"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--max-errors", "2"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn max_warnings() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("unused variable: `unused_in_lib`"),
+      AssertionType::Contains("unused variable: `unused_in_test`"),
+      AssertionType::DoesNotContain("unused variable: `unused_in_beta`"),
+      AssertionType::Contains("*** Warnings: 4 decoded, 1 duplicates, 1 over the limit, 2 shown ***"),
+    ];
+  run_quiet_with_args("workspace-warnings.txt", &["--max-warnings", "2"], 0, &stdout_lines)
+}
+
+
+#[test]
+fn items_and_max_errors_take_the_lower_limit() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/model.rs:68:23"),
+      AssertionType::DoesNotContain("--> src/github.rs:15:69"),
+      AssertionType::Contains("*** Errors: 6 decoded, 5 over the limit, 1 shown ***"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "3", "--max-errors", "1"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn counts_messages_removed_by_each_filter() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("*** Warnings: 4 decoded, 1 removed by target filters, 1 removed by file filters, 1 duplicates, 1 shown ***"),
+    ];
+  run_quiet_with_args("workspace-warnings.txt", &["--items", "10", "--show-warnings", "--target-kind", "lib,test", "--exclude-file", "alpha_tests.rs"], 0, &stdout_lines)
+}
+
+
+#[test]
+fn errors_are_deduplicated_across_targets() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("*** /tmp/dup/src/lib.rs >>> \u{1b}[1m\u{1b}[91merror[E0308]"),
+      AssertionType::Contains("*** error[E0308]: mismatched types at src/lib.rs:4:3 (x2 targets) ***"),
+      AssertionType::Contains("*** Errors: 2 decoded, 1 duplicates, 1 shown ***"),
+    ];
+  run_quiet_with_args("lib-and-test-targets.txt", &["--items", "2"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn warnings_are_deduplicated_across_targets() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("*** /tmp/dup/src/lib.rs (x2 targets) >>> "),
+      AssertionType::Contains("*** Warnings: 2 decoded, 1 duplicates, 1 shown ***"),
+    ];
+  run_quiet_with_args("lib-and-test-targets.txt", &["--items", "2", "--show-warnings"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn repeated_messages_in_ndjson() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#""targets":2}"#),
+      AssertionType::Contains(r#"{"type":"repeated","level":"error","code":"E0308","message":"mismatched types","file_name":"src/lib.rs","line":4,"column":3,"targets":2}"#),
+    ];
+  run_quiet_with_args("lib-and-test-targets.txt", &["--items", "2", "--show-warnings", "--output", "ndjson"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn sort_by_line() {
+  quiet_with_example_input(Path::new(env!("CARGO_TARGET_TMPDIR")), "errors-1.txt")
+    .args(["--items", "5", "--sort", "line"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::is_match(r"(?s)github.rs:15:69.*github.rs:88:23.*github.rs:114:36.*main.rs:56:32.*model.rs:68:23").unwrap());
+}
+
+
+#[test]
+fn sort_by_code() {
+  quiet_with_example_input(Path::new(env!("CARGO_TARGET_TMPDIR")), "errors-1.txt")
+    .args(["--items", "5", "--sort", "code"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::is_match(r"(?s)E0412.*github.rs:15:69.*github.rs:114:36.*main.rs:56:32.*model.rs:68:23.*E0422.*github.rs:88:23").unwrap());
+}
+
+
+#[test]
+fn sorted_errors_are_limited_after_sorting() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/github.rs:15:69"),
+      AssertionType::DoesNotContain("--> src/model.rs:68:23"),
+      AssertionType::Contains("*** Errors: 6 decoded, 5 over the limit, 1 shown ***"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--sort", "file"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn group_by_file() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("---------- src/github.rs: 3 errors, 0 warnings ----------"),
+      AssertionType::Contains("---------- src/main.rs: 1 error, 0 warnings ----------"),
+      AssertionType::DoesNotContain("---------- src/model.rs"),
+      AssertionType::DoesNotContain("*** /Volumes/Work/projects/code/rust/toy/purs/src/main.rs >>>"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "4", "--sort", "line", "--group-by-file"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn summary_only() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("File           Errors  Warnings"),
+      AssertionType::Contains("src/github.rs       3         0"),
+      AssertionType::Contains("(no location)       1         0"),
+      AssertionType::DoesNotContain("--> src/github.rs:15:69"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--summary-only"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn files_in_json_summary() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#""files":[{"file_name":"src/model.rs","errors":1,"warnings":0},{"file_name":"src/github.rs","errors":3,"warnings":0},{"file_name":"src/main.rs","errors":1,"warnings":0},{"file_name":null,"errors":1,"warnings":0}]"#),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "1", "--output", "json"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn internal_compiler_errors_are_not_filtered_out() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("!!! The compiler crashed with an internal compiler error: unexpected panic in type checking !!!"),
+      AssertionType::Contains("!!! There are compilation errors !!!"),
+      AssertionType::DoesNotContain("the compiler unexpectedly panicked"),
+    ];
+  run_quiet_with_args("internal-compiler-error.txt", &["--items", "1", "--file-filter", "lib.rs"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn show_notes() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("the compiler unexpectedly panicked. this is a bug."),
+      AssertionType::Contains("we would appreciate a bug report"),
+      AssertionType::Contains("*** Notes: 2 decoded, 2 shown ***"),
+    ];
+  run_quiet_with_args("internal-compiler-error.txt", &["--items", "1", "--show-notes"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn notes_are_hidden_by_default() {
+  let stdout_lines =
+    [
+      AssertionType::DoesNotContain("For more information about this error"),
+      AssertionType::DoesNotContain("*** Notes:"),
+    ];
+  run_quiet_with_args("lib-and-test-targets.txt", &["--items", "1"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn build_script_failure() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("!!! The build script of build-failure v0.1.0 (/tmp/build-failure) failed !!!"),
+      AssertionType::Contains("could not find libfoo"),
+      AssertionType::DoesNotContain("*** No compilation errors"),
+    ];
+  run_quiet_with_args("build-script-failure.txt", &["--items", "1"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn built_targets() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("*** Built: alpha (lib), alpha_tests (test), beta (bin) ***"),
+    ];
+  run_quiet_with_args("workspace-warnings.txt", &["--items", "1"], 0, &stdout_lines)
+}
+
+
+#[test]
+fn crate_status_when_a_crate_fails() {
+  let project_directory = Path::new(&get_example_file("projects")).join("workspace-failure");
+  quiet_with_example_input(&project_directory, "workspace-failure.txt")
+    .args(["--items", "1"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::is_match(r"(?s)\*\*\* Crates \*\*\*.*alpha: compiled.*beta: failed \(lib\).*gamma: not attempted").unwrap())
+    .stdout(predicate::str::contains("could not compile").not());
+}
+
+
+#[test]
+fn crates_in_json_summary() {
+  let project_directory = Path::new(&get_example_file("projects")).join("workspace-failure");
+  quiet_with_example_input(&project_directory, "workspace-failure.txt")
+    .args(["--items", "1", "--output", "json"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains(r#""crates":[{"name":"alpha","status":"compiled","failed_target":null},{"name":"beta","status":"failed","failed_target":"lib"},{"name":"gamma","status":"not-attempted","failed_target":null}]"#));
+}
+
+
+#[test]
+fn crate_status_from_a_member_of_an_inline_workspace() {
+  let source = Path::new(&get_example_file("projects")).join("workspace-failure");
+  let project_directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("crate_status_from_a_member_of_an_inline_workspace");
+
+  let _ = std::fs::remove_dir_all(&project_directory);
+  ["alpha", "beta", "gamma"]
+    .into_iter()
+    .for_each(|member| {
+      let member_directory = project_directory.join("crates").join(member);
+      std::fs::create_dir_all(&member_directory).expect("Could not create member directory");
+      std::fs::copy(source.join("crates").join(member).join("Cargo.toml"), member_directory.join("Cargo.toml")).expect("Could not copy Cargo.toml");
+    });
+  std::fs::write(project_directory.join("Cargo.toml"), "workspace = { members = [\"crates/*\"], resolver = \"2\" }\n").expect("Could not write Cargo.toml");
+
+  quiet_with_example_input(&project_directory.join("crates/beta"), "workspace-failure.txt")
+    .args(["--items", "1"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::is_match(r"(?s)\*\*\* Crates \*\*\*.*alpha: compiled.*beta: failed \(lib\).*gamma: not attempted").unwrap());
+}
+
+
+#[test]
+fn crate_status_leaves_out_members_of_other_workspaces() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("macro-errors: failed (bin \"macro-errors\")"),
+      AssertionType::DoesNotContain("quiet: not attempted"),
+    ];
+  run_quiet_with_args("macro-errors.txt", &["--items", "1"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn build_in_json_summary() {
+  let stdout_lines =
+    [
+      AssertionType::Contains(r#""build":{"success":false,"built":[],"build_script_failures":["build-failure v0.1.0 (/tmp/build-failure)"],"crates":[]}"#),
+    ];
+  run_quiet_with_args("build-script-failure.txt", &["--items", "1", "--output", "json"], 1, &stdout_lines)
+}
+
+
+#[test]
+fn items_above_255() {
+  let stdout_lines =
+    [
+      AssertionType::Contains("--> src/main.rs:56:32"),
+      AssertionType::Contains("*** Errors: 6 decoded, 6 shown ***"),
+    ];
+  run_quiet_with_args("errors-1.txt", &["--items", "300"], 1, &stdout_lines)
+}
+
+
+const FOCUS_PROFILE: &str = r#"
+[profiles.focus]
+items = 5
+file_filter = ["model.rs"]
+primary_span_only = true
+"#;
+
+
+#[test]
+fn profile_from_project_config() {
+  let project_directory = create_project_with_config("profile_from_project_config", FOCUS_PROFILE);
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .args(["--profile", "focus"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("--> src/model.rs:68:23"))
+    .stdout(predicate::str::contains("--> src/github.rs:15:69").not());
+}
+
+
+#[test]
+fn command_line_overrides_profile() {
+  let project_directory = create_project_with_config("command_line_overrides_profile", FOCUS_PROFILE);
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .args(["--profile", "focus", "--file-filter", "github.rs", "--items", "1"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("--> src/github.rs:15:69"))
+    .stdout(predicate::str::contains("--> src/github.rs:88:23").not())
+    .stdout(predicate::str::contains("--> src/model.rs:68:23").not());
+}
+
+
+#[test]
+fn default_profile_from_user_config() {
+  let project_directory = create_project_with_config("default_profile_from_user_config", "");
+  let user_config_directory = project_directory.join("user-config");
+  std::fs::create_dir_all(user_config_directory.join("quiet")).expect("Could not create user config directory");
+  std::fs::write(user_config_directory.join("quiet/quiet.toml"), "[profiles.default]\nitems = 2\noutput = \"ndjson\"\n").expect("Could not write user config");
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .env("XDG_CONFIG_HOME", &user_config_directory)
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains(r#"{"type":"compiler-message""#).count(2))
+    .stdout(predicate::str::contains(r#"{"type":"summary","errors":true,"warnings":false,"counts":{"errors":{"decoded":6,"removed_by_target_filter":0,"removed_by_file_filter":0,"removed_by_code_filter":0,"duplicates":0,"over_limit":4,"shown":2}"#));
+}
+
+
+#[test]
+fn unknown_profile() {
+  let project_directory = create_project_with_config("unknown_profile", FOCUS_PROFILE);
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .args(["--profile", "refactor"])
+    .assert()
+    .code(4)
+    .stderr(predicate::str::contains("Could not find profile 'refactor' in any quiet.toml"));
+}
+
+
+#[test]
+fn mistakes_in_other_profiles_are_ignored() {
+  let project_directory = create_project_with_config("mistakes_in_other_profiles_are_ignored", "[profiles.old]\nitemz = 1\n");
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .args(["--items", "1"])
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("*** Errors: 6 decoded, 5 over the limit, 1 shown ***"));
+}
+
+
+#[test]
+fn mistakes_in_the_selected_profile_are_reported() {
+  let project_directory = create_project_with_config("mistakes_in_the_selected_profile_are_reported", "[profiles.old]\nitemz = 1\n");
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .args(["--profile", "old"])
+    .assert()
+    .code(4)
+    .stderr(predicate::str::contains("Could not load profile 'old'"))
+    .stderr(predicate::str::contains("unknown field `itemz`"));
+}
+
+
+#[test]
+fn items_is_required_without_profile() {
+  let project_directory = create_project_with_config("items_is_required_without_profile", "");
+
+  quiet_with_example_input(&project_directory, "errors-1.txt")
+    .assert()
+    .code(4)
+    .stderr(predicate::str::contains("--items, --max-errors or --max-warnings is required"));
+}
+
+
+#[test]
+fn input_that_is_not_utf8() {
+  let example_file = get_example_file("errors-1.txt");
+  let mut input = b"before\n\xff\n".to_vec();
+  input.extend(std::fs::read(&example_file).unwrap_or_else(|_| panic!("Could not read file {}", &example_file)));
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .env("XDG_CONFIG_HOME", Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-user-config"))
+    .args(["--items", "1"])
+    .write_stdin(input);
+
+  cmd
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("\u{FFFD}"))
+    .stdout(predicate::str::contains("*** Errors: 6 decoded, 5 over the limit, 1 shown ***"));
+}
+
+
+#[test]
+fn errors_are_printed_before_input_ends() {
+  let example_file = get_example_file("errors-1.txt");
+  let cargo_output =
+    std::fs::read_to_string(example_file.as_str())
+    .unwrap_or_else(|_| panic!("Could not read file {}", &example_file));
+
+  let first_error =
+    cargo_output
+      .lines()
+      .find(|line| line.contains("\"level\":\"error\""))
+      .expect("Could not find an error in example file");
+
+  let mut child =
+    std::process::Command::new(assert_cmd::cargo::cargo_bin("quiet"))
+      .arg("--items")
+      .arg("1")
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .expect("Could not run quiet");
+
+  // Keep stdin open so quiet can't have seen the end of the input.
+  let mut stdin = child.stdin.take().expect("Could not get stdin");
+  writeln!(stdin, "{}", first_error).expect("Could not write to stdin");
+
+  let stdout = child.stdout.take().expect("Could not get stdout");
+  let (sender, receiver) = mpsc::channel();
+  std::thread::spawn(move || {
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+      if sender.send(line).is_err() {
+        break
+      }
+    }
+  });
+
+  let expected = "error[E0412]: cannot find type `PullRequest` in this scope";
+  let found =
+    std::iter::from_fn(|| receiver.recv_timeout(Duration::from_secs(10)).ok())
+      .any(|line| line.contains(expected));
+
+  drop(stdin);
+  child.wait().expect("Could not wait for quiet");
+
+  assert!(found, "{}", s!("Did not see '{}' before the end of input", expected))
+}
+
+
+#[test]
+fn runs_cargo() {
+  let project_directory = copy_example_project("compile-errors", "runs_cargo");
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(&project_directory)
+    .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+    .arg("--items")
+    .arg("1")
+    .arg("run")
+    .arg("--")
+    .arg("check")
+    .arg("-q");
+
+  cmd
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("mismatched types"))
+    .stdout(predicate::str::contains("src/main.rs:3:20"))
+    .stdout(predicate::str::contains("!!! There are compilation errors"));
+}
+
+
+#[test]
+fn runs_cargo_with_toolchain_and_global_options() {
+  let project_directory = copy_example_project("compile-errors", "runs_cargo_with_toolchain_and_global_options");
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(&project_directory)
+    .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+    .args(["--items", "1", "run", "--", "+stable", "--offline", "--color", "never", "check", "-q"]);
+
+  cmd
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("src/main.rs:3:20"))
+    .stdout(predicate::str::contains("!!! There are compilation errors"));
+}
+
+
+#[test]
+fn cargo_that_cannot_be_run() {
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .env("PATH", Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-cargo"))
+    .env("XDG_CONFIG_HOME", Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-user-config"))
+    .args(["--items", "1", "run", "--", "check"]);
+
+  cmd
+    .assert()
+    .code(4)
+    .stderr(predicate::str::contains("Could not run cargo check"));
+}
+
+
+#[cfg(unix)]
+#[test]
+fn runs_cargo_that_prints_bytes_that_are_not_utf8() {
+  use std::os::unix::fs::PermissionsExt;
+
+  // A stand-in for cargo, found first on the PATH
+  let bin_directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("runs_cargo_that_prints_bytes_that_are_not_utf8");
+  let _ = std::fs::remove_dir_all(&bin_directory);
+  std::fs::create_dir_all(&bin_directory).expect("Could not create bin directory");
+  let cargo = bin_directory.join("cargo");
+  std::fs::write(&cargo, "#!/bin/sh\necho before\nprintf '\\377\\n'\necho after\n").expect("Could not write cargo");
+  std::fs::set_permissions(&cargo, std::fs::Permissions::from_mode(0o755)).expect("Could not make cargo executable");
+
+  let path = s!("{}:{}", bin_directory.to_string_lossy(), std::env::var("PATH").unwrap_or_default());
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(&bin_directory)
+    .env("PATH", path)
+    .env("XDG_CONFIG_HOME", bin_directory.join("no-user-config"))
+    .args(["--items", "1", "run", "--", "check"]);
+
+  cmd
+    .assert()
+    .code(0)
+    .stdout(predicate::str::contains("before"))
+    .stdout(predicate::str::contains("\u{FFFD}"))
+    .stdout(predicate::str::contains("after"));
+}
+
+
+#[test]
+fn watch_reruns_cargo_on_changes() {
+  let project_directory = copy_example_project("compile-errors", "watch_reruns_cargo_on_changes");
+  let main_file = project_directory.join("src/main.rs");
+  let manifest = project_directory.join("Cargo.toml");
+  let source = std::fs::read_to_string(&main_file).expect("Could not read main.rs");
+
+  let mut child =
+    std::process::Command::new(assert_cmd::cargo::cargo_bin("quiet"))
+      .current_dir(&project_directory)
+      .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+      .env("XDG_CONFIG_HOME", project_directory.join("no-user-config"))
+      .args(["--items", "1", "watch", "--debounce", "200", "--", "check", "-q"])
+      .stdout(Stdio::piped())
+      .spawn()
+      .expect("Could not run quiet");
+
+  let stdout = child.stdout.take().expect("Could not get stdout");
+  let (sender, receiver) = mpsc::channel();
+  std::thread::spawn(move || {
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+      if line.contains("!!! There are compilation errors") && sender.send(()).is_err() {
+        break
+      }
+    }
+  });
+
+  let run_finished = || receiver.recv_timeout(Duration::from_secs(60)).is_ok();
+  let no_run_within = |timeout| receiver.recv_timeout(timeout).is_err();
+  let check = |passed: bool, failure: &'static str| if passed { Ok(()) } else { Err(failure) };
+
+  // Editors that save by renaming over the manifest should keep triggering runs
+  let save_by_rename = || {
+    let saved = project_directory.join("Cargo.toml.new");
+    std::fs::copy(&manifest, &saved).expect("Could not copy Cargo.toml");
+    std::fs::rename(&saved, &manifest).expect("Could not rename over Cargo.toml");
+  };
+
+  let result =
+    check(run_finished(), "The first run did not finish")
+      // Cargo writes a Cargo.lock on the first run, which should not trigger another run
+      .and_then(|_| check(no_run_within(Duration::from_secs(1)), "Cargo was run again without a change"))
+      .and_then(|_| {
+        // Saving several times in quick succession results in a single run
+        (0..3).for_each(|_| std::fs::write(&main_file, &source).expect("Could not write main.rs"));
+        check(run_finished(), "Changing main.rs did not run Cargo")
+      })
+      .and_then(|_| check(no_run_within(Duration::from_secs(1)), "Changes within the debounce were not combined"))
+      .and_then(|_| {
+        save_by_rename();
+        check(run_finished(), "Saving Cargo.toml did not run Cargo")
+      })
+      .and_then(|_| {
+        save_by_rename();
+        check(run_finished(), "Saving Cargo.toml a second time did not run Cargo")
+      });
+
+  // Stop watching before asserting, so a failure doesn't leave quiet running
+  let _ = child.kill();
+  let _ = child.wait();
+
+  assert_eq!(result, Ok(()))
+}
+
+
+#[cfg(target_os = "linux")]
+#[test]
+fn watch_kills_the_tests_of_a_cancelled_run() {
+  let project_directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("watch_kills_the_tests_of_a_cancelled_run");
+  let pid_file = project_directory.join("test.pid");
+  let main_file = project_directory.join("src/main.rs");
+
+  let _ = std::fs::remove_dir_all(&project_directory);
+  std::fs::create_dir_all(project_directory.join("src")).expect("Could not create project directory");
+  std::fs::write(project_directory.join("Cargo.toml"), "[package]\nname = \"hangs\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").expect("Could not write Cargo.toml");
+  let source = s!(
+    "fn main() {{}}\n\n#[test]\nfn hangs() {{\n  std::fs::write({:?}, std::process::id().to_string()).unwrap();\n  std::thread::sleep(std::time::Duration::from_secs(600));\n}}\n",
+    pid_file.to_string_lossy()
+  );
+  std::fs::write(&main_file, &source).expect("Could not write main.rs");
+
+  let mut child =
+    std::process::Command::new(assert_cmd::cargo::cargo_bin("quiet"))
+      .current_dir(&project_directory)
+      .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+      .env("XDG_CONFIG_HOME", project_directory.join("no-user-config"))
+      .args(["--items", "1", "watch", "--debounce", "200", "--", "test", "-q"])
+      .stdout(Stdio::null())
+      .spawn()
+      .expect("Could not run quiet");
+
+  let wait_for = |condition: &dyn Fn() -> bool| {
+    (0..600).any(|_| {
+      std::thread::sleep(Duration::from_millis(100));
+      condition()
+    })
+  };
+
+  let read_pid = || std::fs::read_to_string(&pid_file).ok().filter(|pid| !pid.is_empty());
+  // A process that has been killed but not yet reaped is a zombie
+  let is_running = |pid: &str| {
+    std::fs::read_to_string(s!("/proc/{}/stat", pid))
+      .map(|stat| !stat.rsplit_once(") ").is_some_and(|(_, rest)| rest.starts_with('Z')))
+      .unwrap_or(false)
+  };
+
+  let first_pid = wait_for(&|| read_pid().is_some()).then(read_pid).flatten();
+
+  let result =
+    match &first_pid {
+      None => Err("The test of the first run did not start"),
+      Some(first_pid) => {
+        let _ = std::fs::remove_file(&pid_file);
+        std::fs::write(&main_file, &source).expect("Could not write main.rs");
+
+        if !wait_for(&|| read_pid().is_some()) {
+          Err("The test of the second run did not start")
+        } else if !wait_for(&|| !is_running(first_pid)) {
+          Err("The test of the cancelled run is still running")
+        } else {
+          Ok(())
+        }
+      },
+    };
+
+  // Stop watching and the test that is still running before asserting
+  let _ = child.kill();
+  let _ = child.wait();
+  first_pid
+    .into_iter()
+    .chain(read_pid())
+    .for_each(|pid| {
+      let _ = std::process::Command::new("kill").args(["-9", pid.as_str()]).status();
+    });
+
+  assert_eq!(result, Ok(()))
+}
+
+
+#[test]
+fn fixes_shown_messages_only() {
+  let project_directory = copy_example_project("fixable", "fixes_shown_messages_only");
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(&project_directory)
+    .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+    .args(["--items", "1", "--show-warnings", "--fix", "run", "--", "check", "-q"]);
+
+  cmd
+    .assert()
+    .code(0)
+    .stdout(predicate::str::contains("-  let mut first = 1;"))
+    .stdout(predicate::str::contains("+  let first = 1;"))
+    .stdout(predicate::str::contains("Applied 1 fix(es) to 1 file(s)"));
+
+  let fixed = std::fs::read_to_string(project_directory.join("src/main.rs")).expect("Could not read fixed file");
+  assert!(fixed.contains("  let first = 1;"));
+  assert!(fixed.contains("  let mut second = 2;"));
+}
+
+
+#[test]
+fn fixes_files_relative_to_the_workspace_root() {
+  let project_directory = copy_example_project("fixable", "fixes_files_relative_to_the_workspace_root");
+  let member_directory = project_directory.join("member");
+  let member_main = "fn main() {\n  println!(\"The member's main.rs should not be changed by fixes to the root package\");\n}\n";
+
+  let manifest = std::fs::read_to_string(project_directory.join("Cargo.toml")).expect("Could not read Cargo.toml");
+  std::fs::write(project_directory.join("Cargo.toml"), s!("{}\n[workspace]\nmembers = [\"member\"]\n", manifest)).expect("Could not write Cargo.toml");
+  std::fs::create_dir_all(member_directory.join("src")).expect("Could not create member directory");
+  std::fs::write(member_directory.join("Cargo.toml"), "[package]\nname = \"member\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").expect("Could not write member Cargo.toml");
+  std::fs::write(member_directory.join("src/main.rs"), member_main).expect("Could not write member main.rs");
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(&member_directory)
+    .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+    .env("XDG_CONFIG_HOME", project_directory.join("no-user-config"))
+    .args(["--items", "2", "--show-warnings", "--fix", "run", "--", "check", "-q", "--workspace"]);
+
+  cmd
+    .assert()
+    .code(0)
+    .stdout(predicate::str::contains("Applied 2 fix(es) to 1 file(s)"));
+
+  let fixed = std::fs::read_to_string(project_directory.join("src/main.rs")).expect("Could not read fixed file");
+  assert!(fixed.contains("  let first = 1;"));
+  assert!(fixed.contains("  let second = 2;"));
+
+  let unchanged = std::fs::read_to_string(member_directory.join("src/main.rs")).expect("Could not read member main.rs");
+  assert_eq!(member_main, unchanged);
+}
+
+
+#[test]
+fn fix_dry_run() {
+  let project_directory = copy_example_project("fixable", "fix_dry_run");
+  let original = std::fs::read_to_string(project_directory.join("src/main.rs")).expect("Could not read file");
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(&project_directory)
+    .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+    .args(["--items", "2", "--show-warnings", "--fix", "--dry-run", "run", "--", "check", "-q"]);
+
+  cmd
+    .assert()
+    .code(0)
+    .stdout(predicate::str::contains("+  let first = 1;"))
+    .stdout(predicate::str::contains("+  let second = 2;"))
+    .stdout(predicate::str::contains("Dry run: 2 fix(es) to 1 file(s) not applied"));
+
+  let unchanged = std::fs::read_to_string(project_directory.join("src/main.rs")).expect("Could not read file");
+  assert_eq!(original, unchanged);
+}
+
+
+#[test]
+fn fix_requires_text_output() {
+  let project_directory = copy_example_project("fixable", "fix_requires_text_output");
+  let original = std::fs::read_to_string(project_directory.join("src/main.rs")).expect("Could not read file");
+
+  ["json", "ndjson"]
+    .into_iter()
+    .for_each(|output| {
+      let mut cmd = Command::cargo_bin("quiet").unwrap();
+      cmd
+        .current_dir(&project_directory)
+        .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+        .args(["--items", "2", "--show-warnings", "--fix", "--output", output, "run", "--", "check", "-q"]);
+
+      cmd
+        .assert()
+        .code(4)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("--fix can only be used with text output"));
+    });
+
+  let unchanged = std::fs::read_to_string(project_directory.join("src/main.rs")).expect("Could not read file");
+  assert_eq!(original, unchanged);
+}
+
+
+fn run_quiet<P: AsRef<Path>>(cargo_output_file: P, exit_code: i32, stdout_assertions: &[AssertionType]) {
+  run_quiet_with_args(cargo_output_file, &["--items", "1"], exit_code, stdout_assertions)
+}
+
+
+fn run_quiet_with_args<P: AsRef<Path>>(cargo_output_file: P, args: &[&str], exit_code: i32, stdout_assertions: &[AssertionType]) {
   let mut cmd = Command::cargo_bin("quiet").unwrap();
 
   let stdout_contains = |expected: &str| {
@@ -160,19 +1365,20 @@ fn run_quiet<P: AsRef<Path>>(cargo_output_file: P, stdout_assertions: &[Assertio
   let example_file = get_example_file(&cargo_output_file);
   let cargo_output =
     std::fs::read(example_file.as_str())
-    .expect(&s!("Could not read file {}", &example_file));
+    .unwrap_or_else(|_| panic!("Could not read file {}", &example_file));
 
   let input = std::str::from_utf8(&cargo_output).expect("Could not decode output");
 
+  // Don't pick up the user's own profiles
   cmd
-    .arg("--items")
-    .arg("1")
+    .env("XDG_CONFIG_HOME", Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-user-config"))
+    .args(args)
     .write_stdin(input);
 
   let mut asserts =
     cmd
       .assert()
-      .success();
+      .code(exit_code);
 
   let lines_should_exist =
     stdout_assertions
@@ -217,3 +1423,45 @@ fn get_example_file<P: AsRef<Path>>(file: P) -> String {
 
   example_file.to_string_lossy().to_string()
 }
+
+
+/// Copies an example project so it can be changed by a test.
+fn copy_example_project(project: &str, test_name: &str) -> PathBuf {
+  let source = Path::new(&get_example_file("projects")).join(project);
+  let destination = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test_name);
+
+  let _ = std::fs::remove_dir_all(&destination);
+  std::fs::create_dir_all(destination.join("src")).expect("Could not create project directory");
+  std::fs::copy(source.join("Cargo.toml"), destination.join("Cargo.toml")).expect("Could not copy Cargo.toml");
+  std::fs::copy(source.join("src/main.rs"), destination.join("src/main.rs")).expect("Could not copy main.rs");
+
+  destination
+}
+
+
+/// Creates a project directory with a quiet.toml. The Cargo.toml marks it as the project root.
+fn create_project_with_config(test_name: &str, quiet_toml: &str) -> PathBuf {
+  let destination = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test_name);
+
+  let _ = std::fs::remove_dir_all(&destination);
+  std::fs::create_dir_all(&destination).expect("Could not create project directory");
+  std::fs::write(destination.join("Cargo.toml"), "[package]\nname = \"config\"\nversion = \"0.1.0\"\n").expect("Could not write Cargo.toml");
+  std::fs::write(destination.join("quiet.toml"), quiet_toml).expect("Could not write quiet.toml");
+
+  destination
+}
+
+
+/// Runs quiet from the supplied directory, isolated from the user's own config.
+fn quiet_with_example_input(directory: &Path, cargo_output_file: &str) -> Command {
+  let example_file = get_example_file(cargo_output_file);
+  let input = std::fs::read_to_string(&example_file).unwrap_or_else(|_| panic!("Could not read file {}", &example_file));
+
+  let mut cmd = Command::cargo_bin("quiet").unwrap();
+  cmd
+    .current_dir(directory)
+    .env("XDG_CONFIG_HOME", directory.join("no-user-config"))
+    .write_stdin(input);
+
+  cmd
+}